@@ -54,7 +54,8 @@ pub unsafe extern "system" fn Java_graphics_pathfinder_pathfinderdemo_Pathfinder
         backing_scale_factor: 1.0,
     };
     let window = WindowImpl { size: logical_size };
-    let options = Options::default();
+    let mut options = Options::default();
+    options.command_line_overrides();
 
     JAVA_ACTIVITY.with(|java_activity| {
         *java_activity.borrow_mut() = Some(JavaActivity::new(env.clone(), activity));
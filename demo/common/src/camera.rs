@@ -18,14 +18,22 @@ use pathfinder_geometry::basic::vector::{Vector2F, Vector2I, Vector4F};
 use pathfinder_geometry::basic::rect::RectF;
 use pathfinder_geometry::basic::transform2d::Transform2DF;
 use pathfinder_geometry::basic::transform3d::{Perspective, Transform3DF};
+use pathfinder_gpu::DepthFunc;
+use serde::{Deserialize, Serialize};
 use std::f32::consts::FRAC_PI_4;
 
 const NEAR_CLIP_PLANE: f32 = 0.01;
 const FAR_CLIP_PLANE: f32 = 10.0;
 
+// How far the 3D camera starts from the scene, in units of half the view box's smaller side.
+const INITIAL_VIEW_DISTANCE: f32 = 3.0;
+
 // Half of the eye separation distance.
 const DEFAULT_EYE_OFFSET: f32 = 0.025;
 
+// How much of the scene, in device pixels, is kept on screen when panning in 2D.
+const MIN_VISIBLE_SCENE_SIZE_2D: f32 = 64.0;
+
 pub enum Camera {
     TwoD(Transform2DF),
     ThreeD {
@@ -44,11 +52,16 @@ pub enum Camera {
 }
 
 impl Camera {
-    pub fn new(mode: Mode, view_box: RectF, viewport_size: Vector2I) -> Camera {
+    pub fn new(mode: Mode,
+               view_box: RectF,
+               viewport_size: Vector2I,
+               up_axis: UpAxis,
+               projection: Projection)
+               -> Camera {
         if mode == Mode::TwoD {
             Camera::new_2d(view_box, viewport_size)
         } else {
-            Camera::new_3d(mode, view_box, viewport_size)
+            Camera::new_3d(mode, view_box, viewport_size, up_axis, projection)
         }
     }
 
@@ -59,13 +72,32 @@ impl Camera {
         Camera::TwoD(Transform2DF::from_scale(Vector2F::splat(scale)).post_translate(origin))
     }
 
-    fn new_3d(mode: Mode, view_box: RectF, viewport_size: Vector2I) -> Camera {
+    fn new_3d(mode: Mode,
+              view_box: RectF,
+              viewport_size: Vector2I,
+              up_axis: UpAxis,
+              projection: Projection)
+              -> Camera {
         let viewport_count = mode.viewport_count();
 
         let fov_y = FRAC_PI_4;
         let aspect = viewport_size.x() as f32 / viewport_size.y() as f32;
-        let projection =
-            Transform3DF::from_perspective(fov_y, aspect, NEAR_CLIP_PLANE, FAR_CLIP_PLANE);
+        let projection = match projection {
+            Projection::Perspective => {
+                Transform3DF::from_perspective(fov_y, aspect, NEAR_CLIP_PLANE, FAR_CLIP_PLANE)
+            }
+            Projection::Orthographic => {
+                // Frame the scene as the perspective projection would from the starting position.
+                let half_height = INITIAL_VIEW_DISTANCE * (fov_y * 0.5).tan();
+                let half_width = half_height * aspect;
+                Transform3DF::from_ortho(-half_width,
+                                         half_width,
+                                         -half_height,
+                                         half_height,
+                                         NEAR_CLIP_PLANE,
+                                         FAR_CLIP_PLANE)
+            }
+        };
         let perspective = Perspective::new(&projection, viewport_size);
 
         // Create a scene transform by moving the camera back from the center of the eyes so that
@@ -95,11 +127,49 @@ impl Camera {
         Camera::ThreeD {
             scene_transform,
             eye_transforms,
-            modelview_transform: CameraTransform3D::new(view_box),
+            modelview_transform: CameraTransform3D::new(view_box, up_axis),
             velocity: Vector4F::default(),
         }
     }
 
+    pub fn state(&self) -> CameraState {
+        match *self {
+            Camera::TwoD(ref transform) => {
+                let translation = transform.translation();
+                CameraState::TwoD {
+                    transform: [transform.m11(),
+                                transform.m12(),
+                                transform.m21(),
+                                transform.m22(),
+                                translation.x(),
+                                translation.y()],
+                }
+            }
+            Camera::ThreeD { ref modelview_transform, .. } => modelview_transform.state(),
+        }
+    }
+
+    /// Restores a state returned by `state()`. The camera stops moving. States for a different
+    /// kind of camera (2D versus 3D) are ignored.
+    pub fn set_state(&mut self, state: CameraState) {
+        match (self, state) {
+            (&mut Camera::TwoD(ref mut transform), CameraState::TwoD { transform: t }) => {
+                *transform = Transform2DF::row_major(t[0], t[1], t[2], t[3], t[4], t[5]);
+            }
+            (&mut Camera::ThreeD { ref mut modelview_transform, ref mut velocity, .. },
+             CameraState::ThreeD { position, yaw, pitch, scale }) => {
+                let position = Vector4F::new(position[0], position[1], position[2], 1.0);
+                let up_axis = modelview_transform.up_axis;
+                modelview_transform.position = up_axis.view_to_world().transform_point(position);
+                modelview_transform.yaw = yaw;
+                modelview_transform.pitch = pitch;
+                modelview_transform.scale = scale;
+                *velocity = Vector4F::default();
+            }
+            _ => {}
+        }
+    }
+
     pub fn is_3d(&self) -> bool {
         match *self {
             Camera::ThreeD { .. } => true,
@@ -124,29 +194,34 @@ pub struct CameraTransform3D {
     pub yaw: f32,
     pub pitch: f32,
     scale: f32,
+    up_axis: UpAxis,
 }
 
 impl CameraTransform3D {
-    fn new(view_box: RectF) -> CameraTransform3D {
+    fn new(view_box: RectF, up_axis: UpAxis) -> CameraTransform3D {
         let scale = scale_factor_for_view_box(view_box);
+        let view_position = Vector4F::new(
+            0.5 * view_box.max_x(),
+            -0.5 * view_box.max_y(),
+            0.5 * INITIAL_VIEW_DISTANCE / scale,
+            1.0,
+        );
         CameraTransform3D {
-            position: Vector4F::new(
-                0.5 * view_box.max_x(),
-                -0.5 * view_box.max_y(),
-                1.5 / scale,
-                1.0,
-            ),
+            position: up_axis.view_to_world().transform_point(view_position),
             yaw: 0.0,
             pitch: 0.0,
             scale,
+            up_axis,
         }
     }
 
+    // The vector is in view space, so that forward is always -Z whichever way is up.
     pub fn offset(&mut self, vector: Vector4F) -> bool {
         let update = !vector.is_zero();
         if update {
             let rotation = Transform3DF::from_rotation(-self.yaw, -self.pitch, 0.0);
-            self.position = self.position + rotation.transform_point(vector);
+            let vector = rotation.transform_point(vector);
+            self.position = self.position + self.up_axis.view_to_world().transform_point(vector);
         }
         update
     }
@@ -154,16 +229,74 @@ impl CameraTransform3D {
     pub fn to_transform(&self) -> Transform3DF {
         let mut transform = Transform3DF::from_rotation(self.yaw, self.pitch, 0.0);
         transform = transform.post_mul(&Transform3DF::from_uniform_scale(2.0 * self.scale));
+        transform = transform.post_mul(&self.up_axis.view_to_world().inverse());
         transform = transform.post_mul(&Transform3DF::from_translation(
             -self.position.x(),
             -self.position.y(),
             -self.position.z(),
         ));
+        transform.post_mul(&self.up_axis.svg_to_world())
+    }
 
-        // Flip Y.
-        transform = transform.post_mul(&Transform3DF::from_scale(1.0, -1.0, 1.0));
+    fn state(&self) -> CameraState {
+        let position = self.up_axis.view_to_world().inverse().transform_point(self.position);
+        CameraState::ThreeD {
+            position: [position.x(), position.y(), position.z()],
+            yaw: self.yaw,
+            pitch: self.pitch,
+            scale: self.scale,
+        }
+    }
+}
+
+/// A snapshot of the camera that can be saved and restored later, to reproduce a view.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum CameraState {
+    /// The 2D camera transform, as `[m11, m12, m21, m22, m31, m32]`.
+    TwoD { transform: [f32; 6] },
+    /// The 3D camera. The position is in view space, so it doesn't depend on the up axis.
+    ThreeD { position: [f32; 3], yaw: f32, pitch: f32, scale: f32 },
+}
+
+/// How the 3D camera projects the scene onto the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    /// Keeps parallel lines parallel, with no foreshortening, which suits technical drawings.
+    Orthographic,
+}
+
+/// The world axis that points up in 3D mode.
+///
+/// The SVG stands upright on the ground either way. This only changes the world coordinate system
+/// that the camera position and movement are expressed in, so the rendered image doesn't change.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpAxis {
+    Y,
+    Z,
+}
 
-        transform
+impl UpAxis {
+    // Maps SVG coordinates, which are Y-down with the page at Z = 0, to world coordinates.
+    fn svg_to_world(self) -> Transform3DF {
+        match self {
+            UpAxis::Y => Transform3DF::from_scale(1.0, -1.0, 1.0),
+            UpAxis::Z => Transform3DF::row_major(1.0,  0.0,  0.0, 0.0,
+                                                 0.0,  0.0, -1.0, 0.0,
+                                                 0.0, -1.0,  0.0, 0.0,
+                                                 0.0,  0.0,  0.0, 1.0),
+        }
+    }
+
+    // Maps view coordinates, which are Y-up and looking down -Z, to world coordinates.
+    fn view_to_world(self) -> Transform3DF {
+        match self {
+            UpAxis::Y => Transform3DF::default(),
+            UpAxis::Z => Transform3DF::row_major(1.0, 0.0,  0.0, 0.0,
+                                                 0.0, 0.0, -1.0, 0.0,
+                                                 0.0, 1.0,  0.0, 0.0,
+                                                 0.0, 0.0,  0.0, 1.0),
+        }
     }
 }
 
@@ -188,8 +321,144 @@ impl Mode {
             Mode::VR => View::Stereo(viewport),
         }
     }
+
+    /// Returns the depth comparison that the ground and the vector scene are drawn with, or
+    /// `None` if this mode draws without depth testing.
+    pub fn depth_func(self) -> Option<DepthFunc> {
+        match self {
+            Mode::TwoD => None,
+            Mode::ThreeD | Mode::VR => Some(DepthFunc::Less),
+        }
+    }
 }
 
 pub fn scale_factor_for_view_box(view_box: RectF) -> f32 {
     1.0 / f32::min(view_box.size().x(), view_box.size().y())
 }
+
+/// Returns a 2D camera transform that centers the view box in the viewport and scales it to fill
+/// the viewport, less a margin of `margin` times the viewport size on each side.
+pub fn fit_2d_transform(view_box: RectF, viewport_size: Vector2I, margin: f32) -> Transform2DF {
+    let viewport_size = viewport_size.to_f32();
+    let available_size = viewport_size.scale(1.0 - 2.0 * margin);
+    let scale = f32::min(available_size.x() / view_box.size().x(),
+                         available_size.y() / view_box.size().y());
+    let view_box_center = view_box.origin() + view_box.size().scale(0.5);
+    Transform2DF::from_scale(Vector2F::splat(scale))
+        .post_translate(viewport_size.scale(0.5) - view_box_center.scale(scale))
+}
+
+/// Translates the given 2D camera transform by the smallest amount that keeps at least a small
+/// part of the view box within the viewport.
+pub fn clamp_2d_pan(transform: &Transform2DF, view_box: RectF, viewport_size: Vector2I)
+                    -> Transform2DF {
+    let scene_rect = transform.transform_rect(&view_box);
+    let viewport_size = viewport_size.to_f32();
+    let margin = Vector2F::splat(MIN_VISIBLE_SCENE_SIZE_2D).min(scene_rect.size());
+    let delta = Vector2F::new(clamp_delta(scene_rect.min_x(),
+                                          scene_rect.max_x(),
+                                          viewport_size.x(),
+                                          margin.x()),
+                              clamp_delta(scene_rect.min_y(),
+                                          scene_rect.max_y(),
+                                          viewport_size.y(),
+                                          margin.y()));
+    if delta.is_zero() {
+        *transform
+    } else {
+        transform.post_translate(delta)
+    }
+}
+
+fn clamp_delta(scene_min: f32, scene_max: f32, viewport_max: f32, margin: f32) -> f32 {
+    if scene_max < margin {
+        margin - scene_max
+    } else if scene_min > viewport_max - margin {
+        viewport_max - margin - scene_min
+    } else {
+        0.0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Camera, CameraState, CameraTransform3D, Mode, Projection, UpAxis};
+    use pathfinder_geometry::basic::rect::RectF;
+    use pathfinder_geometry::basic::vector::{Vector2F, Vector2I, Vector4F};
+
+    #[test]
+    fn test_fit_2d_transform() {
+        let view_box = RectF::new(Vector2F::new(100.0, 50.0), Vector2F::new(400.0, 200.0));
+        let viewport_size = Vector2I::new(1000, 1000);
+
+        // The wider dimension fills the viewport, less the margin, and the scene is centered.
+        let transform = super::fit_2d_transform(view_box, viewport_size, 0.1);
+        let fitted_rect = transform.transform_rect(&view_box);
+        assert!(fitted_rect.origin().to_3d().approx_eq(&Vector4F::new(100.0, 300.0, 0.0, 1.0),
+                                                       0.001));
+        assert!(fitted_rect.size().to_3d().approx_eq(&Vector4F::new(800.0, 400.0, 0.0, 1.0),
+                                                     0.001));
+    }
+
+    #[test]
+    fn test_camera_state() {
+        let view_box = RectF::new(Vector2F::default(), Vector2F::new(400.0, 300.0));
+        let mut camera = Camera::new(Mode::TwoD,
+                                     view_box,
+                                     Vector2I::new(800, 600),
+                                     UpAxis::Y,
+                                     Projection::Perspective);
+        let state = camera.state();
+        camera.set_state(CameraState::TwoD { transform: [2.0, 0.0, 0.0, 2.0, 10.0, 20.0] });
+        assert_ne!(camera.state(), state);
+        camera.set_state(state);
+        assert_eq!(camera.state(), state);
+
+        // 3D states are independent of the up axis.
+        for &up_axis in &[UpAxis::Y, UpAxis::Z] {
+            let mut camera = Camera::new(Mode::ThreeD,
+                                         view_box,
+                                         Vector2I::new(800, 600),
+                                         up_axis,
+                                         Projection::Perspective);
+            let state = CameraState::ThreeD {
+                position: [1.0, 2.0, 3.0],
+                yaw: 0.5,
+                pitch: -0.25,
+                scale: 0.01,
+            };
+            camera.set_state(state);
+            match camera.state() {
+                CameraState::ThreeD { position, yaw, pitch, scale } => {
+                    let position = Vector4F::new(position[0], position[1], position[2], 1.0);
+                    assert!(position.approx_eq(&Vector4F::new(1.0, 2.0, 3.0, 1.0), 0.0001));
+                    assert_eq!((yaw, pitch, scale), (0.5, -0.25, 0.01));
+                }
+                CameraState::TwoD { .. } => panic!("expected a 3D camera state"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_up_axis() {
+        let view_box = RectF::new(Vector2F::default(), Vector2F::new(400.0, 300.0));
+        let bottom_left = Vector4F::new(0.0, 300.0, 0.0, 1.0);
+
+        // The bottom edge of the SVG is below the origin, along the up axis.
+        let y_up_point = UpAxis::Y.svg_to_world().transform_point(bottom_left);
+        assert!(y_up_point.approx_eq(&Vector4F::new(0.0, -300.0, 0.0, 1.0), 0.0001));
+        let z_up_point = UpAxis::Z.svg_to_world().transform_point(bottom_left);
+        assert!(z_up_point.approx_eq(&Vector4F::new(0.0, 0.0, -300.0, 1.0), 0.0001));
+
+        // The camera sees the SVG the same way whichever axis is up, including after moving.
+        let mut y_up_camera = CameraTransform3D::new(view_box, UpAxis::Y);
+        let mut z_up_camera = CameraTransform3D::new(view_box, UpAxis::Z);
+        for &velocity in &[Vector4F::default(), Vector4F::new(10.0, 0.0, -20.0, 0.0)] {
+            y_up_camera.offset(velocity);
+            z_up_camera.offset(velocity);
+            let y_up_point = y_up_camera.to_transform().transform_point(bottom_left);
+            let z_up_point = z_up_camera.to_transform().transform_point(bottom_left);
+            assert!(y_up_point.approx_eq(&z_up_point, 0.0001));
+        }
+    }
+}
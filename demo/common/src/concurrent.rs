@@ -12,24 +12,41 @@
 
 use pathfinder_renderer::concurrent::executor::{Executor, SequentialExecutor};
 use pathfinder_renderer::concurrent::rayon::RayonExecutor;
-use rayon::ThreadPoolBuilder;
+use rayon::{ThreadPool, ThreadPoolBuilder};
 
 pub struct DemoExecutor {
     sequential_mode: bool,
+    // A private pool, used only if the global Rayon pool was already started elsewhere with
+    // settings we can't rely on.
+    thread_pool: Option<ThreadPool>,
 }
 
 impl DemoExecutor {
+    /// Creates an executor that runs on `thread_count` threads, or one per CPU if `None`.
+    ///
+    /// With a thread count of 1, all work runs on the calling thread and the Rayon thread pool is
+    /// never started, which keeps profiles and debugging sessions deterministic.
+    ///
+    /// Otherwise, this starts the global Rayon thread pool. If that pool has already been started
+    /// (by the host application, or by another `DemoApp`), it's reused, unless a specific thread
+    /// count was requested, in which case the executor creates a pool of its own.
     pub fn new(thread_count: Option<usize>) -> DemoExecutor {
         let sequential_mode = thread_count == Some(1);
+        let mut thread_pool = None;
         if !sequential_mode {
-            let mut thread_pool_builder = ThreadPoolBuilder::new();
-            if let Some(thread_count) = thread_count {
-                thread_pool_builder = thread_pool_builder.num_threads(thread_count);
+            let thread_pool_builder = || {
+                let mut thread_pool_builder = ThreadPoolBuilder::new();
+                if let Some(thread_count) = thread_count {
+                    thread_pool_builder = thread_pool_builder.num_threads(thread_count);
+                }
+                thread_pool_builder
+            };
+            if thread_pool_builder().build_global().is_err() && thread_count.is_some() {
+                thread_pool = Some(thread_pool_builder().build().unwrap());
             }
-            thread_pool_builder.build_global().unwrap();
         }
 
-        DemoExecutor { sequential_mode }
+        DemoExecutor { sequential_mode, thread_pool }
     }
 }
 
@@ -38,8 +55,44 @@ impl Executor for DemoExecutor {
                                  where T: Send, F: Fn(usize) -> Vec<T> + Send + Sync {
         if self.sequential_mode {
             SequentialExecutor.flatten_into_vector(length, builder)
+        } else if let Some(ref thread_pool) = self.thread_pool {
+            thread_pool.install(|| RayonExecutor.flatten_into_vector(length, builder))
         } else {
             RayonExecutor.flatten_into_vector(length, builder)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::DemoExecutor;
+    use pathfinder_renderer::concurrent::executor::Executor;
+    use std::thread;
+
+    #[test]
+    fn test_sequential_mode_stays_on_calling_thread() {
+        let executor = DemoExecutor::new(Some(1));
+        let calling_thread = thread::current().id();
+        let results = executor.flatten_into_vector(64, |index| {
+            vec![(index, thread::current().id(), rayon::current_thread_index())]
+        });
+        assert_eq!(results.len(), 64);
+        for (expected_index, &(index, thread_id, rayon_index)) in results.iter().enumerate() {
+            assert_eq!(index, expected_index);
+            assert_eq!(thread_id, calling_thread);
+            assert_eq!(rayon_index, None);
+        }
+    }
+
+    #[test]
+    fn test_global_thread_pool_already_started() {
+        // Whichever of these starts the global pool first, the others must not panic.
+        let executors =
+            vec![DemoExecutor::new(None), DemoExecutor::new(Some(2)), DemoExecutor::new(Some(3))];
+        assert!(executors[2].thread_pool.is_some());
+        for executor in &executors {
+            let results = executor.flatten_into_vector(16, |index| vec![index]);
+            assert_eq!(results, (0..16).collect::<Vec<_>>());
+        }
+    }
+}
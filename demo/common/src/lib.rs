@@ -14,45 +14,77 @@
 extern crate log;
 
 // Mode is used in Options, so has to be public
-pub use crate::camera::Mode;
+pub use crate::camera::{CameraState, Mode, Projection, UpAxis};
 
 use crate::camera::Camera;
 use crate::concurrent::DemoExecutor;
 use crate::device::{GroundProgram, GroundVertexArray};
 use crate::ui::{DemoUIModel, DemoUIPresenter, ScreenshotInfo, ScreenshotType, UIAction};
-use crate::window::{Event, Keycode, SVGPath, Window, WindowSize};
+use crate::watcher::SVGWatcher;
+use crate::window::{Event, GamepadAxis, GamepadButton, Keycode, OcularTransform, SVGPath};
+use crate::window::{Window, WindowSize};
 use clap::{App, Arg};
 use pathfinder_geometry::basic::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::basic::rect::RectF;
 use pathfinder_geometry::basic::transform2d::Transform2DF;
-use pathfinder_geometry::basic::transform3d::Transform3DF;
+use pathfinder_geometry::basic::transform3d::{Perspective, Transform3DF};
 use pathfinder_geometry::color::ColorU;
 use pathfinder_gl::GLDevice;
 use pathfinder_gpu::Device;
 use pathfinder_gpu::resources::ResourceLoader;
 use pathfinder_renderer::concurrent::scene_proxy::{RenderCommandStream, SceneProxy};
 use pathfinder_renderer::gpu::renderer::{DestFramebuffer, RenderStats, RenderTime, Renderer};
+use pathfinder_renderer::gpu_data::RenderCommand;
 use pathfinder_renderer::options::{RenderOptions, RenderTransform};
 use pathfinder_renderer::post::STEM_DARKENING_FACTORS;
 use pathfinder_renderer::scene::Scene;
 use pathfinder_svg::BuiltSVG;
 use pathfinder_ui::{MousePosition, UIEvent};
-use std::fs::File;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use serde::Serialize;
+use std::env;
+use std::fmt::{self, Display, Formatter};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, LineWriter, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::process;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use usvg::{Options as UsvgOptions, Tree};
 
 static DEFAULT_SVG_VIRTUAL_PATH: &'static str = "svg/Ghostscript_Tiger.svg";
-
-const MOUSELOOK_ROTATION_SPEED: f32 = 0.007;
-const CAMERA_VELOCITY: f32 = 0.02;
-
-// How much the scene is scaled when a scale gesture is performed.
-const CAMERA_SCALE_SPEED_2D: f32 = 6.0;
-// How much the scene is scaled when a zoom button is clicked.
-const CAMERA_ZOOM_AMOUNT_2D: f32 = 0.1;
+// Names a resource to load instead of the tiger when no input path is given.
+static DEFAULT_SVG_ENV_VAR: &'static str = "PATHFINDER_DEFAULT_SVG";
+
+// Radians of 3D camera rotation per pixel of mouse movement, unless overridden by
+// `--mouse-speed`.
+const DEFAULT_MOUSELOOK_ROTATION_SPEED: f32 = 0.007;
+// How fast the 3D camera moves while a movement key is held, unless overridden by `--move-speed`.
+const DEFAULT_CAMERA_VELOCITY: f32 = 0.02;
+
+// How much the scene is scaled when a scale gesture is performed, unless overridden by
+// `--zoom-speed`.
+const DEFAULT_CAMERA_SCALE_SPEED_2D: f32 = 6.0;
+// How much the scene is scaled when a zoom button is clicked, unless overridden by `--zoom-step`.
+const DEFAULT_CAMERA_ZOOM_AMOUNT_2D: f32 = 0.1;
+
+// Bounds on how much a single scroll event can scale the scene, so that a large delta can't
+// shrink the scene to nothing or flip it over.
+const MIN_SCROLL_ZOOM_SCALE: f32 = 0.5;
+const MAX_SCROLL_ZOOM_SCALE: f32 = 2.0;
+
+// How many device pixels the scene moves per frame while a movement key is held in 2D mode.
+const PAN_SPEED_2D: f32 = 12.0;
+
+// Gamepad stick deflections smaller than this fraction of the full range are ignored, so that a
+// stick at rest doesn't drift the camera.
+const GAMEPAD_DEAD_ZONE: f32 = 0.2;
+// Radians of 3D camera rotation per frame while the right stick is fully deflected.
+const GAMEPAD_LOOK_SPEED: f32 = 0.03;
+
+// The fraction of the window left empty on each side of the scene when fitting it to the window,
+// unless overridden by `--fit-margin`.
+const DEFAULT_FIT_MARGIN: f32 = 0.05;
 
 // Half of the eye separation distance.
 const DEFAULT_EYE_OFFSET: f32 = 0.025;
@@ -76,6 +108,27 @@ const TRANSPARENT_BG_COLOR: ColorU = ColorU {
     a: 0,
 };
 
+const DEFAULT_GROUND_COLOR: ColorU = ColorU {
+    r: 80,
+    g: 80,
+    b: 80,
+    a: 255,
+};
+const DEFAULT_GRIDLINE_COLOR: ColorU = ColorU {
+    r: 127,
+    g: 127,
+    b: 127,
+    a: 255,
+};
+
+// The first few frames pay for shader compilation and buffer allocation, so they aren't counted
+// toward the statistics by default.
+const DEFAULT_WARMUP_FRAMES: u32 = 3;
+
+// With level of detail enabled, paths smaller than this many device pixels across are skipped in
+// 2D mode.
+const LOD_THRESHOLD: f32 = 1.0;
+
 const APPROX_FONT_SIZE: f32 = 16.0;
 
 const MESSAGE_TIMEOUT_SECS: u64 = 5;
@@ -87,6 +140,7 @@ mod concurrent;
 mod device;
 mod renderer;
 mod ui;
+mod watcher;
 
 pub struct DemoApp<W> where W: Window {
     pub window: W,
@@ -98,18 +152,30 @@ pub struct DemoApp<W> where W: Window {
     scene_metadata: SceneMetadata,
     render_transform: Option<RenderTransform>,
     render_command_stream: Option<RenderCommandStream>,
+    last_render_commands: Vec<RenderCommand>,
 
     camera: Camera,
     frame_counter: u32,
     pending_screenshot_info: Option<ScreenshotInfo>,
     mouselook_enabled: bool,
+    pan_velocity_2d: Vector2F,
+    gamepad_look_velocity: Vector2F,
     pub dirty: bool,
     expire_message_event_id: u32,
     message_epoch: u32,
+    svg_changed_event_id: u32,
+    svg_watcher: SVGWatcher,
+    // The SVG files in the input directory, if a directory was given, and which one is shown.
+    directory_svg_paths: Vec<PathBuf>,
+    directory_svg_index: usize,
     last_mouse_position: Vector2I,
+    held_events: Vec<Event>,
 
     current_frame: Option<Frame>,
     build_time: Option<Duration>,
+    present_time: Option<Duration>,
+    last_frame_end_time: Option<Instant>,
+    stats_output: Option<LineWriter<File>>,
 
     ui_model: DemoUIModel,
     ui_presenter: DemoUIPresenter<GLDevice>,
@@ -124,19 +190,69 @@ pub struct DemoApp<W> where W: Window {
 }
 
 impl<W> DemoApp<W> where W: Window {
+    /// Creates the demo with `options` as given. Embedders that accept command-line flags should
+    /// call `Options::command_line_overrides()` first.
     pub fn new(window: W, window_size: WindowSize, mut options: Options) -> DemoApp<W> {
         let expire_message_event_id = window.create_user_event_id();
+        let svg_changed_event_id = window.create_user_event_id();
 
         let device = GLDevice::new(window.gl_version(), window.gl_default_framebuffer());
+        let adapter_info = device.adapter_info();
+        eprintln!("GPU: {} ({}), API version {}",
+                  adapter_info.renderer,
+                  adapter_info.vendor,
+                  adapter_info.version);
         let resources = window.resource_loader();
 
-        // Read command line options.
-        options.command_line_overrides();
+        // If the input is a directory, start with its first SVG file.
+        let directory_svg_paths = match options.input_path {
+            SVGPath::Path(ref path) if path.is_dir() => {
+                match list_svg_files(path) {
+                    Ok(ref paths) if paths.is_empty() => {
+                        eprintln!("`{}` doesn't contain any SVG files", path.display());
+                        process::exit(1);
+                    }
+                    Ok(paths) => paths,
+                    Err(err) => {
+                        eprintln!("Couldn't list `{}`: {}", path.display(), err);
+                        process::exit(1);
+                    }
+                }
+            }
+            _ => vec![],
+        };
+        if let Some(first_path) = directory_svg_paths.first() {
+            options.input_path = SVGPath::Path(first_path.clone());
+        }
+
+        // A screenshot requested on the command line is taken of the first frame.
+        let pending_screenshot_info = options.screenshot_path.clone().map(|path| {
+            ScreenshotInfo {
+                kind: ScreenshotType::from_path(&path),
+                path,
+                scale: options.screenshot_scale,
+            }
+        });
+
+        let stats_output = options.stats_output.as_ref().and_then(|path| {
+            match OpenOptions::new().create(true).append(true).open(path) {
+                Ok(file) => Some(LineWriter::new(file)),
+                Err(err) => {
+                    eprintln!("Couldn't open the stats output file {}: {}; not writing stats",
+                              path.display(),
+                              err);
+                    None
+                }
+            }
+        });
 
         // Set up the executor.
         let executor = DemoExecutor::new(options.jobs);
 
-        let mut built_svg = load_scene(resources, &options.input_path);
+        let mut built_svg = load_scene(resources, &options.input_path).unwrap_or_else(|err| {
+            eprintln!("Couldn't load {}: {}", describe_svg_path(&options.input_path), err);
+            process::exit(1);
+        });
         let message = get_svg_building_message(&built_svg);
 
         let viewport = window.viewport(options.mode.view(0));
@@ -148,7 +264,11 @@ impl<W> DemoApp<W> where W: Window {
         let renderer = Renderer::new(device, resources, dest_framebuffer);
         let scene_metadata = SceneMetadata::new_clipping_view_box(&mut built_svg.scene,
                                                                   viewport.size());
-        let camera = Camera::new(options.mode, scene_metadata.view_box, viewport.size());
+        let camera = Camera::new(options.mode,
+                                 scene_metadata.view_box,
+                                 viewport.size(),
+                                 options.up_axis,
+                                 options.projection);
 
         let scene_proxy = SceneProxy::from_scene(built_svg.scene, executor);
 
@@ -170,7 +290,27 @@ impl<W> DemoApp<W> where W: Window {
 
         let ui_presenter = DemoUIPresenter::new(&renderer.device, resources);
 
-        DemoApp {
+        let camera_state = options.camera_state_path.as_ref().and_then(|path| {
+            match load_camera_state(path) {
+                Ok(camera_state) => Some(camera_state),
+                Err(err) => {
+                    eprintln!("Couldn't load the camera state from {}: {}; using the default \
+                               camera",
+                              path.display(),
+                              err);
+                    None
+                }
+            }
+        });
+
+        let mut svg_watcher = SVGWatcher::default();
+        if options.watch {
+            if let SVGPath::Path(ref path) = options.input_path {
+                svg_watcher.watch::<W>(path.clone(), svg_changed_event_id);
+            }
+        }
+
+        let mut app = DemoApp {
             window,
             should_exit: false,
             options,
@@ -180,18 +320,29 @@ impl<W> DemoApp<W> where W: Window {
             scene_metadata,
             render_transform: None,
             render_command_stream: None,
+            last_render_commands: vec![],
 
             camera,
             frame_counter: 0,
-            pending_screenshot_info: None,
+            pending_screenshot_info,
             mouselook_enabled: false,
+            pan_velocity_2d: Vector2F::default(),
+            gamepad_look_velocity: Vector2F::default(),
             dirty: true,
             expire_message_event_id,
             message_epoch,
+            svg_changed_event_id,
+            svg_watcher,
+            directory_svg_paths,
+            directory_svg_index: 0,
             last_mouse_position: Vector2I::default(),
+            held_events: vec![],
 
             current_frame: None,
             build_time: None,
+            present_time: None,
+            last_frame_end_time: None,
+            stats_output,
 
             ui_presenter,
             ui_model,
@@ -203,6 +354,60 @@ impl<W> DemoApp<W> where W: Window {
 
             ground_program,
             ground_vertex_array,
+        };
+
+        if let Some(camera_state) = camera_state {
+            app.set_camera_state(camera_state);
+        }
+
+        app
+    }
+
+    /// Returns a snapshot of the current camera, which `set_camera_state()` can restore.
+    pub fn camera_state(&self) -> CameraState {
+        self.camera.state()
+    }
+
+    /// Restores a camera snapshot, switching between 2D and 3D mode if necessary.
+    pub fn set_camera_state(&mut self, state: CameraState) {
+        let mode = match state {
+            CameraState::TwoD { .. } => Mode::TwoD,
+            CameraState::ThreeD { .. } if self.ui_model.mode == Mode::TwoD => Mode::ThreeD,
+            CameraState::ThreeD { .. } => self.ui_model.mode,
+        };
+        if mode != self.ui_model.mode {
+            self.ui_model.mode = mode;
+            self.reset_camera();
+        }
+
+        self.camera.set_state(state);
+        self.dirty = true;
+    }
+
+    /// In single-step mode, holds events back until the space bar is pressed, so that each press
+    /// renders exactly one frame. Returns the events to render the next frame with, or `None` if
+    /// the app should keep idling.
+    pub fn step_events(&mut self, events: Vec<Event>) -> Option<Vec<Event>> {
+        if !self.options.single_step {
+            return Some(events);
+        }
+
+        let mut step = false;
+        for event in events {
+            match event {
+                Event::KeyDown(Keycode::Space) => step = true,
+                Event::Quit { .. } | Event::KeyDown(Keycode::Escape) => {
+                    self.held_events.push(event);
+                    step = true;
+                }
+                _ => self.held_events.push(event),
+            }
+        }
+
+        if step {
+            Some(mem::replace(&mut self.held_events, vec![]))
+        } else {
+            None
         }
     }
 
@@ -227,6 +432,14 @@ impl<W> DemoApp<W> where W: Window {
     }
 
     fn build_scene(&mut self) {
+        if !self.pan_velocity_2d.is_zero() {
+            if let Camera::TwoD(ref mut transform) = self.camera {
+                *transform = transform.post_translate(self.pan_velocity_2d);
+                self.dirty = true;
+            }
+            self.clamp_pan();
+        }
+
         self.render_transform = match self.camera {
             Camera::ThreeD {
                 ref scene_transform,
@@ -237,6 +450,11 @@ impl<W> DemoApp<W> where W: Window {
                 if modelview_transform.offset(*velocity) {
                     self.dirty = true;
                 }
+                if !self.gamepad_look_velocity.is_zero() {
+                    modelview_transform.yaw += self.gamepad_look_velocity.x();
+                    modelview_transform.pitch += self.gamepad_look_velocity.y();
+                    self.dirty = true;
+                }
                 let perspective = scene_transform
                     .perspective
                     .post_mul(&scene_transform.modelview_to_eye)
@@ -246,19 +464,24 @@ impl<W> DemoApp<W> where W: Window {
             Camera::TwoD(transform) => Some(RenderTransform::Transform2D(transform)),
         };
 
-        let render_options = RenderOptions {
-            transform: self.render_transform.clone().unwrap(),
+        let render_options = self.render_options(self.render_transform.clone().unwrap(), 1.0);
+        self.render_command_stream = Some(self.scene_proxy.build_with_stream(render_options));
+    }
+
+    // `scale` is the number of device pixels per window device pixel being rendered to.
+    fn render_options(&self, transform: RenderTransform, scale: f32) -> RenderOptions {
+        RenderOptions {
+            transform,
             dilation: if self.ui_model.stem_darkening_effect_enabled {
-                let font_size = APPROX_FONT_SIZE * self.window_size.backing_scale_factor;
+                let font_size = APPROX_FONT_SIZE * self.window_size.backing_scale_factor * scale;
                 let (x, y) = (STEM_DARKENING_FACTORS[0], STEM_DARKENING_FACTORS[1]);
                 Vector2F::new(x, y).scale(font_size)
             } else {
                 Vector2F::default()
             },
             subpixel_aa_enabled: self.ui_model.subpixel_aa_effect_enabled,
-        };
-
-        self.render_command_stream = Some(self.scene_proxy.build_with_stream(render_options));
+            lod_threshold: if self.options.lod { LOD_THRESHOLD } else { 0.0 },
+        }
     }
 
     fn handle_events(&mut self, events: Vec<Event>) -> Vec<UIEvent> {
@@ -268,7 +491,7 @@ impl<W> DemoApp<W> where W: Window {
         for event in events {
             match event {
                 Event::Quit { .. } | Event::KeyDown(Keycode::Escape) => {
-                    self.should_exit = true;
+                    self.exit();
                     self.dirty = true;
                 }
                 Event::WindowResized(new_size) => {
@@ -294,7 +517,7 @@ impl<W> DemoApp<W> where W: Window {
                         let rotation = mouse_position
                             .relative
                             .to_f32()
-                            .scale(MOUSELOOK_ROTATION_SPEED);
+                            .scale(self.options.mouse_speed);
                         modelview_transform.yaw += rotation.x();
                         modelview_transform.pitch += rotation.y();
                         self.dirty = true;
@@ -310,10 +533,25 @@ impl<W> DemoApp<W> where W: Window {
                         let backing_scale_factor = self.window_size.backing_scale_factor;
                         let position = position.to_f32().scale(backing_scale_factor);
                         *transform = transform.post_translate(-position);
-                        let scale_delta = 1.0 + d_dist * CAMERA_SCALE_SPEED_2D;
+                        let scale_delta = 1.0 + d_dist * self.options.zoom_speed;
+                        *transform = transform.post_scale(Vector2F::splat(scale_delta));
+                        *transform = transform.post_translate(position);
+                    }
+                    self.clamp_pan();
+                }
+                Event::Scroll(delta, position) => {
+                    if let Camera::TwoD(ref mut transform) = self.camera {
+                        let backing_scale_factor = self.window_size.backing_scale_factor;
+                        let position = position.to_f32().scale(backing_scale_factor);
+                        let scale_delta = (1.0 + delta * self.options.zoom_step)
+                            .max(MIN_SCROLL_ZOOM_SCALE)
+                            .min(MAX_SCROLL_ZOOM_SCALE);
+                        *transform = transform.post_translate(-position);
                         *transform = transform.post_scale(Vector2F::splat(scale_delta));
                         *transform = transform.post_translate(position);
+                        self.dirty = true;
                     }
+                    self.clamp_pan();
                 }
                 Event::Look { pitch, yaw } => {
                     if let Camera::ThreeD {
@@ -333,6 +571,10 @@ impl<W> DemoApp<W> where W: Window {
                     } = self.camera
                     {
                         *eye_transforms = new_eye_transforms;
+                        if self.options.eye_offset_adjustment != 0.0 {
+                            adjust_eye_offsets(eye_transforms,
+                                               self.options.eye_offset_adjustment);
+                        }
                         // Calculate the new scene transform by lerp'ing the eye transforms.
                         *scene_transform = eye_transforms[0];
                         for (index, eye_transform) in eye_transforms.iter().enumerate().skip(1) {
@@ -347,68 +589,103 @@ impl<W> DemoApp<W> where W: Window {
                     }
                 }
                 Event::KeyDown(Keycode::Alphanumeric(b'w')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        let scale_factor =
-                            camera::scale_factor_for_view_box(self.scene_metadata.view_box);
-                        velocity.set_z(-CAMERA_VELOCITY / scale_factor);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_z(-self.options.move_speed / scale_factor);
+                        }
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_y(PAN_SPEED_2D),
                     }
+                    self.dirty = true;
                 }
                 Event::KeyDown(Keycode::Alphanumeric(b's')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        let scale_factor =
-                            camera::scale_factor_for_view_box(self.scene_metadata.view_box);
-                        velocity.set_z(CAMERA_VELOCITY / scale_factor);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_z(self.options.move_speed / scale_factor);
+                        }
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_y(-PAN_SPEED_2D),
                     }
+                    self.dirty = true;
                 }
                 Event::KeyDown(Keycode::Alphanumeric(b'a')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        let scale_factor =
-                            camera::scale_factor_for_view_box(self.scene_metadata.view_box);
-                        velocity.set_x(-CAMERA_VELOCITY / scale_factor);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_x(-self.options.move_speed / scale_factor);
+                        }
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_x(PAN_SPEED_2D),
                     }
+                    self.dirty = true;
                 }
                 Event::KeyDown(Keycode::Alphanumeric(b'd')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        let scale_factor =
-                            camera::scale_factor_for_view_box(self.scene_metadata.view_box);
-                        velocity.set_x(CAMERA_VELOCITY / scale_factor);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_x(self.options.move_speed / scale_factor);
+                        }
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_x(-PAN_SPEED_2D),
                     }
+                    self.dirty = true;
                 }
                 Event::KeyUp(Keycode::Alphanumeric(b'w'))
                 | Event::KeyUp(Keycode::Alphanumeric(b's')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        velocity.set_z(0.0);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => velocity.set_z(0.0),
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_y(0.0),
                     }
+                    self.dirty = true;
                 }
                 Event::KeyUp(Keycode::Alphanumeric(b'a'))
                 | Event::KeyUp(Keycode::Alphanumeric(b'd')) => {
-                    if let Camera::ThreeD {
-                        ref mut velocity, ..
-                    } = self.camera
-                    {
-                        velocity.set_x(0.0);
-                        self.dirty = true;
+                    match self.camera {
+                        Camera::ThreeD { ref mut velocity, .. } => velocity.set_x(0.0),
+                        Camera::TwoD(_) => self.pan_velocity_2d.set_x(0.0),
                     }
+                    self.dirty = true;
+                }
+                Event::GamepadAxis { axis, value } => {
+                    let value = apply_gamepad_dead_zone(value);
+                    match (axis, &mut self.camera) {
+                        (GamepadAxis::LeftX, &mut Camera::ThreeD { ref mut velocity, .. }) => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_x(value * self.options.move_speed / scale_factor);
+                        }
+                        (GamepadAxis::LeftY, &mut Camera::ThreeD { ref mut velocity, .. }) => {
+                            let scale_factor =
+                                camera::scale_factor_for_view_box(self.scene_metadata.view_box);
+                            velocity.set_z(value * self.options.move_speed / scale_factor);
+                        }
+                        (GamepadAxis::LeftX, &mut Camera::TwoD(_)) => {
+                            self.pan_velocity_2d.set_x(-value * PAN_SPEED_2D)
+                        }
+                        (GamepadAxis::LeftY, &mut Camera::TwoD(_)) => {
+                            self.pan_velocity_2d.set_y(-value * PAN_SPEED_2D)
+                        }
+                        (GamepadAxis::RightX, _) => {
+                            self.gamepad_look_velocity.set_x(value * GAMEPAD_LOOK_SPEED)
+                        }
+                        (GamepadAxis::RightY, _) => {
+                            self.gamepad_look_velocity.set_y(value * GAMEPAD_LOOK_SPEED)
+                        }
+                    }
+                    self.dirty = true;
+                }
+                Event::GamepadButton { button: GamepadButton::Start, pressed: true } => {
+                    self.reset_camera();
+                    self.dirty = true;
+                }
+                Event::KeyDown(Keycode::Alphanumeric(b'f')) => {
+                    self.handle_ui_action(&mut UIAction::FitToWindow);
+                }
+                Event::KeyDown(Keycode::Alphanumeric(b'r')) => {
+                    self.reset_camera();
+                    self.dirty = true;
                 }
                 Event::KeyDown(Keycode::Tab) => {
                     self.options.ui = match self.options.ui {
@@ -418,20 +695,36 @@ impl<W> DemoApp<W> where W: Window {
                     }
                 }
 
-                Event::OpenSVG(ref svg_path) => {
-                    let mut built_svg = load_scene(self.window.resource_loader(), svg_path);
-                    self.ui_model.message = get_svg_building_message(&built_svg);
-
-                    let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
-                    self.scene_metadata =
-                        SceneMetadata::new_clipping_view_box(&mut built_svg.scene, viewport_size);
-                    self.camera = Camera::new(self.ui_model.mode,
-                                              self.scene_metadata.view_box,
-                                              viewport_size);
+                Event::KeyDown(Keycode::PageDown) if !self.directory_svg_paths.is_empty() => {
+                    self.open_directory_svg(1);
+                }
+                Event::KeyDown(Keycode::PageUp) if !self.directory_svg_paths.is_empty() => {
+                    self.open_directory_svg(-1);
+                }
 
-                    self.scene_proxy.replace_scene(built_svg.scene);
+                Event::OpenSVG(ref svg_path) => {
+                    self.open_svg(svg_path);
+                }
 
-                    self.dirty = true;
+                Event::User {
+                    message_type: event_id,
+                    message_data: generation,
+                } if event_id == self.svg_changed_event_id
+                    && self.svg_watcher.is_current(generation) =>
+                {
+                    let path = self.svg_watcher.path().unwrap().to_owned();
+                    let svg_path = SVGPath::Path(path.clone());
+                    match load_scene(self.window.resource_loader(), &svg_path) {
+                        Ok(built_svg) => self.open_scene(built_svg),
+                        Err(err) => {
+                            let message = format!("Couldn't reload `{}`: {}", path.display(), err);
+                            emit_message::<W>(&mut self.ui_model,
+                                              &mut self.message_epoch,
+                                              self.expire_message_event_id,
+                                              message);
+                            self.dirty = true;
+                        }
+                    }
                 }
 
                 Event::User {
@@ -450,6 +743,77 @@ impl<W> DemoApp<W> where W: Window {
         ui_events
     }
 
+    // Loads and shows the SVG, or shows a message explaining why it couldn't be loaded. Returns
+    // true if the SVG was loaded.
+    fn open_svg(&mut self, svg_path: &SVGPath) -> bool {
+        match load_scene(self.window.resource_loader(), svg_path) {
+            Ok(built_svg) => {
+                self.open_scene(built_svg);
+                match *svg_path {
+                    SVGPath::Path(ref path) if self.options.watch => {
+                        self.svg_watcher.watch::<W>(path.clone(), self.svg_changed_event_id)
+                    }
+                    _ => self.svg_watcher.unwatch(),
+                }
+                true
+            }
+            Err(err) => {
+                let message = format!("Couldn't open {}: {}", describe_svg_path(svg_path), err);
+                emit_message::<W>(&mut self.ui_model,
+                                  &mut self.message_epoch,
+                                  self.expire_message_event_id,
+                                  message);
+                self.dirty = true;
+                false
+            }
+        }
+    }
+
+    // Moves `offset` files forward (or backward, if negative) through the input directory,
+    // wrapping around at either end.
+    fn open_directory_svg(&mut self, offset: isize) {
+        let count = self.directory_svg_paths.len() as isize;
+        let index = ((self.directory_svg_index as isize + offset) % count + count) % count;
+        let index = index as usize;
+        self.directory_svg_index = index;
+
+        let path = self.directory_svg_paths[index].clone();
+        if self.open_svg(&SVGPath::Path(path.clone())) && self.ui_model.message.is_empty() {
+            let name = path.file_name().unwrap_or_else(|| path.as_os_str()).to_string_lossy();
+            let message = format!("{} ({} of {})", name, index + 1, count);
+            emit_message::<W>(&mut self.ui_model,
+                              &mut self.message_epoch,
+                              self.expire_message_event_id,
+                              message);
+        }
+    }
+
+    fn open_scene(&mut self, mut built_svg: BuiltSVG) {
+        self.ui_model.message = get_svg_building_message(&built_svg);
+
+        let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
+        self.scene_metadata =
+            SceneMetadata::new_clipping_view_box(&mut built_svg.scene, viewport_size);
+        self.reset_camera();
+
+        self.scene_proxy.replace_scene(built_svg.scene);
+        self.last_render_commands.clear();
+
+        self.dirty = true;
+    }
+
+    // Returns the camera to the initial framing of the scene in the current mode.
+    fn reset_camera(&mut self) {
+        self.pan_velocity_2d = Vector2F::default();
+        self.gamepad_look_velocity = Vector2F::default();
+        let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
+        self.camera = Camera::new(self.ui_model.mode,
+                                  self.scene_metadata.view_box,
+                                  viewport_size,
+                                  self.options.up_axis,
+                                  self.options.projection);
+    }
+
     fn process_mouse_position(&mut self, new_position: Vector2I) -> MousePosition {
         let absolute = new_position.scale(self.window_size.backing_scale_factor as i32);
         let relative = absolute - self.last_mouse_position;
@@ -488,8 +852,45 @@ impl<W> DemoApp<W> where W: Window {
 
         self.handle_ui_events(frame, &mut ui_action);
 
+        let present_start_time = Instant::now();
         self.window.present();
+        self.present_time = Some(Instant::now() - present_start_time);
         self.frame_counter += 1;
+
+        // Headless runs render a single frame, which has now been saved.
+        if self.options.headless {
+            self.exit();
+        }
+
+        self.limit_frame_rate();
+    }
+
+    fn exit(&mut self) {
+        self.should_exit = true;
+
+        if let Some(ref path) = self.options.save_camera_state_path {
+            if let Err(err) = save_camera_state(path, &self.camera_state()) {
+                eprintln!("Couldn't save the camera state to {}: {}", path.display(), err);
+            }
+        }
+    }
+
+    // Sleeps off whatever is left of the frame budget if a target frame rate was set. If the app
+    // was idle waiting for events, the budget has already run out and this returns immediately.
+    fn limit_frame_rate(&mut self) {
+        let target_fps = match self.options.target_fps {
+            None => return,
+            Some(target_fps) => target_fps,
+        };
+
+        let frame_budget = Duration::from_secs(1) / target_fps;
+        if let Some(last_frame_end_time) = self.last_frame_end_time {
+            let elapsed = Instant::now() - last_frame_end_time;
+            if elapsed < frame_budget {
+                thread::sleep(frame_budget - elapsed);
+            }
+        }
+        self.last_frame_end_time = Some(Instant::now());
     }
 
     fn update_stats(&mut self) {
@@ -502,10 +903,30 @@ impl<W> DemoApp<W> where W: Window {
             return
         }
 
-        let build_time = self.build_time.unwrap();
-
         let zero = RenderStats::default();
         let aggregate_stats = frame.scene_stats.iter().fold(zero, |sum, item| sum + *item);
+
+        // The current frame hasn't been presented yet, so this is the previous frame's time.
+        let present_time = self.present_time.unwrap_or_default();
+
+        if self.options.single_step {
+            println!("frame {}: {} paths, {} fills, {} alpha tiles, {} solid tiles, \
+                      {:?} present",
+                     self.frame_counter,
+                     aggregate_stats.path_count,
+                     aggregate_stats.fill_count,
+                     aggregate_stats.alpha_tile_count,
+                     aggregate_stats.solid_tile_count,
+                     present_time);
+        }
+
+        // Frames still warming up are rendered, but their stats are thrown away.
+        if self.frame_counter < self.options.warmup_frames {
+            return
+        }
+
+        let build_time = self.build_time.unwrap();
+
         let total_rendering_time = if frame.scene_rendering_times.is_empty() {
             None
         } else {
@@ -517,18 +938,31 @@ impl<W> DemoApp<W> where W: Window {
             )
         };
 
+        if let Some(ref mut stats_output) = self.stats_output {
+            let record = StatsRecord::new(self.frame_counter,
+                                          &aggregate_stats,
+                                          build_time,
+                                          total_rendering_time,
+                                          present_time);
+            if let Err(err) = write_stats_line(stats_output, &record) {
+                eprintln!("Couldn't write to the stats output file: {}; not writing stats", err);
+                self.stats_output = None;
+            }
+        }
+
         self.renderer.debug_ui_presenter.add_sample(aggregate_stats,
                                                     build_time,
-                                                    total_rendering_time);
+                                                    total_rendering_time,
+                                                    present_time);
     }
 
     fn maybe_take_screenshot(&mut self) {
         match self.pending_screenshot_info.take() {
             None => {}
-            Some(ScreenshotInfo { kind: ScreenshotType::PNG, path }) => {
-                self.take_raster_screenshot(path)
+            Some(ScreenshotInfo { kind: ScreenshotType::Raster(format), path, scale }) => {
+                self.take_raster_screenshot(path, format, scale)
             }
-            Some(ScreenshotInfo { kind: ScreenshotType::SVG, path }) => {
+            Some(ScreenshotInfo { kind: ScreenshotType::SVG, path, .. }) => {
                 // FIXME(pcwalton): This won't work on Android.
                 File::create(path).unwrap().write_all(&mut self.scene_proxy.as_svg()).unwrap();
             }
@@ -544,10 +978,7 @@ impl<W> DemoApp<W> where W: Window {
         //
         // FIXME(pcwalton): This should really be an MVC setup.
         if self.camera.mode() != self.ui_model.mode {
-            let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
-            self.camera = Camera::new(self.ui_model.mode,
-                                      self.scene_metadata.view_box,
-                                      viewport_size);
+            self.reset_camera();
         }
 
         for ui_event in frame.ui_events {
@@ -560,6 +991,7 @@ impl<W> DemoApp<W> where W: Window {
                     if let Camera::TwoD(ref mut transform) = self.camera {
                         *transform = transform.post_translate(position.relative.to_f32());
                     }
+                    self.clamp_pan();
                 }
                 _ => {}
             }
@@ -576,7 +1008,7 @@ impl<W> DemoApp<W> where W: Window {
             }
             UIAction::ZoomIn => {
                 if let Camera::TwoD(ref mut transform) = self.camera {
-                    let scale = Vector2F::splat(1.0 + CAMERA_ZOOM_AMOUNT_2D);
+                    let scale = Vector2F::splat(1.0 + self.options.zoom_step);
                     let center = center_of_window(&self.window_size);
                     *transform = transform
                         .post_translate(-center)
@@ -584,10 +1016,11 @@ impl<W> DemoApp<W> where W: Window {
                         .post_translate(center);
                     self.dirty = true;
                 }
+                self.clamp_pan();
             }
             UIAction::ZoomOut => {
                 if let Camera::TwoD(ref mut transform) = self.camera {
-                    let scale = Vector2F::splat(1.0 - CAMERA_ZOOM_AMOUNT_2D);
+                    let scale = Vector2F::splat(1.0 - self.options.zoom_step);
                     let center = center_of_window(&self.window_size);
                     *transform = transform
                         .post_translate(-center)
@@ -595,6 +1028,16 @@ impl<W> DemoApp<W> where W: Window {
                         .post_translate(center);
                     self.dirty = true;
                 }
+                self.clamp_pan();
+            }
+            UIAction::FitToWindow => {
+                if let Camera::TwoD(ref mut transform) = self.camera {
+                    let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
+                    *transform = camera::fit_2d_transform(self.scene_metadata.view_box,
+                                                          viewport_size,
+                                                          self.options.fit_margin);
+                    self.dirty = true;
+                }
             }
             UIAction::ZoomActualSize => {
                 if let Camera::TwoD(ref mut transform) = self.camera {
@@ -615,11 +1058,47 @@ impl<W> DemoApp<W> where W: Window {
         }
     }
 
+    /// Returns the camera transform from scene to device pixel coordinates in 2D mode, or `None`
+    /// in 3D and VR modes.
+    pub fn current_2d_transform(&self) -> Option<Transform2DF> {
+        match self.camera {
+            Camera::TwoD(transform) => Some(transform),
+            Camera::ThreeD { .. } => None,
+        }
+    }
+
+    /// Returns the composed perspective and view transform that the scene is rendered with in 3D
+    /// and VR modes, or `None` in 2D mode.
+    pub fn current_3d_transform(&self) -> Option<Perspective> {
+        match self.camera {
+            Camera::ThreeD { ref scene_transform, ref modelview_transform, .. } => {
+                Some(scene_transform.perspective
+                                    .post_mul(&scene_transform.modelview_to_eye)
+                                    .post_mul(&modelview_transform.to_transform()))
+            }
+            Camera::TwoD(_) => None,
+        }
+    }
+
+    // Keeps some of the scene visible in 2D mode, if pan clamping is enabled.
+    fn clamp_pan(&mut self) {
+        if !self.options.clamp_pan {
+            return;
+        }
+        if let Camera::TwoD(ref mut transform) = self.camera {
+            let viewport_size = self.window.viewport(self.ui_model.mode.view(0)).size();
+            *transform = camera::clamp_2d_pan(transform,
+                                              self.scene_metadata.view_box,
+                                              viewport_size);
+        }
+    }
+
     fn background_color(&self) -> ColorU {
         match self.ui_model.background_color {
             BackgroundColor::Light => LIGHT_BG_COLOR,
             BackgroundColor::Dark => DARK_BG_COLOR,
             BackgroundColor::Transparent => TRANSPARENT_BG_COLOR,
+            BackgroundColor::Custom(color) => color,
         }
     }
 }
@@ -631,6 +1110,31 @@ pub struct Options {
     pub input_path: SVGPath,
     pub ui: UIVisibility,
     pub background_color: BackgroundColor,
+    pub clamp_pan: bool,
+    pub ground_color: ColorU,
+    pub gridline_color: ColorU,
+    pub warmup_frames: u32,
+    pub single_step: bool,
+    pub zoom_step: f32,
+    pub zoom_speed: f32,
+    pub mouse_speed: f32,
+    pub move_speed: f32,
+    pub fit_margin: f32,
+    pub letterbox_color: Option<ColorU>,
+    pub lod: bool,
+    pub up_axis: UpAxis,
+    pub projection: Projection,
+    pub headless: bool,
+    pub screenshot_path: Option<PathBuf>,
+    pub screenshot_scale: u32,
+    pub watch: bool,
+    pub stats_output: Option<PathBuf>,
+    pub target_fps: Option<u32>,
+    pub camera_state_path: Option<PathBuf>,
+    pub save_camera_state_path: Option<PathBuf>,
+    /// Extra distance, in metres, to move each eye outward from the pose reported by an immersive
+    /// display (or inward, if negative). Zero leaves the reported eye geometry untouched.
+    pub eye_offset_adjustment: f32,
     hidden_field_for_future_proofing: (),
 }
 
@@ -642,13 +1146,36 @@ impl Default for Options {
             input_path: SVGPath::Default,
             ui: UIVisibility::All,
             background_color: BackgroundColor::Light,
+            clamp_pan: true,
+            ground_color: DEFAULT_GROUND_COLOR,
+            gridline_color: DEFAULT_GRIDLINE_COLOR,
+            warmup_frames: DEFAULT_WARMUP_FRAMES,
+            single_step: false,
+            zoom_step: DEFAULT_CAMERA_ZOOM_AMOUNT_2D,
+            zoom_speed: DEFAULT_CAMERA_SCALE_SPEED_2D,
+            mouse_speed: DEFAULT_MOUSELOOK_ROTATION_SPEED,
+            move_speed: DEFAULT_CAMERA_VELOCITY,
+            fit_margin: DEFAULT_FIT_MARGIN,
+            letterbox_color: None,
+            lod: false,
+            up_axis: UpAxis::Y,
+            projection: Projection::Perspective,
+            headless: false,
+            screenshot_path: None,
+            screenshot_scale: 1,
+            watch: false,
+            stats_output: None,
+            target_fps: None,
+            camera_state_path: None,
+            save_camera_state_path: None,
+            eye_offset_adjustment: 0.0,
             hidden_field_for_future_proofing: (),
         }
     }
 }
 
 impl Options {
-    fn command_line_overrides(&mut self) {
+    pub fn command_line_overrides(&mut self) {
         let matches = App::new("tile-svg")
             .arg(
                 Arg::with_name("jobs")
@@ -656,7 +1183,8 @@ impl Options {
                     .long("jobs")
                     .value_name("THREADS")
                     .takes_value(true)
-                    .help("Number of threads to use"),
+                    .validator(validate_jobs)
+                    .help("Number of threads to use (1 disables multithreading entirely)"),
             )
             .arg(
                 Arg::with_name("3d")
@@ -685,18 +1213,184 @@ impl Options {
                     .short("b")
                     .long("background")
                     .takes_value(true)
-                    .possible_values(&["light", "dark", "transparent"])
-                    .help("The background color to use"),
+                    .validator(validate_background_color)
+                    .help("The background color to use: light, dark, transparent, or hex \
+                           (e.g. #1e1e2e)"),
+            )
+            .arg(
+                Arg::with_name("ground-color")
+                    .long("ground-color")
+                    .value_name("COLOR")
+                    .takes_value(true)
+                    .validator(validate_hex_color)
+                    .help("The color of the ground in 3D mode, as hex (e.g. #505050)"),
+            )
+            .arg(
+                Arg::with_name("grid-color")
+                    .long("grid-color")
+                    .value_name("COLOR")
+                    .takes_value(true)
+                    .validator(validate_hex_color)
+                    .help("The color of the ground gridlines in 3D mode, as hex (e.g. #7f7f7f)"),
+            )
+            .arg(
+                Arg::with_name("letterbox-color")
+                    .long("letterbox-color")
+                    .value_name("COLOR")
+                    .takes_value(true)
+                    .validator(validate_hex_color)
+                    .help("The color of the margins around the scene in 2D mode, as hex"),
+            )
+            .arg(
+                Arg::with_name("warmup-frames")
+                    .long("warmup-frames")
+                    .value_name("FRAMES")
+                    .takes_value(true)
+                    .validator(validate_frame_count)
+                    .help("Number of initial frames to leave out of the stats (default 3, 0 to \
+                           count every frame)"),
+            )
+            .arg(
+                Arg::with_name("step")
+                    .long("step")
+                    .help("Render one frame each time the space bar is pressed"),
+            )
+            .arg(
+                Arg::with_name("zoom-step")
+                    .long("zoom-step")
+                    .value_name("AMOUNT")
+                    .takes_value(true)
+                    .validator(validate_zoom_step)
+                    .help("How much the zoom buttons scale the scene in 2D mode (default 0.1)"),
+            )
+            .arg(
+                Arg::with_name("zoom-speed")
+                    .long("zoom-speed")
+                    .value_name("SPEED")
+                    .takes_value(true)
+                    .validator(validate_zoom_speed)
+                    .help("How fast zoom gestures scale the scene in 2D mode (default 6.0)"),
+            )
+            .arg(
+                Arg::with_name("mouse-speed")
+                    .long("mouse-speed")
+                    .value_name("SPEED")
+                    .takes_value(true)
+                    .validator(validate_camera_speed)
+                    .help("How fast the mouse turns the camera in 3D mode (default 0.007)"),
+            )
+            .arg(
+                Arg::with_name("move-speed")
+                    .long("move-speed")
+                    .value_name("SPEED")
+                    .takes_value(true)
+                    .validator(validate_camera_speed)
+                    .help("How fast the movement keys move the camera in 3D mode (default 0.02)"),
+            )
+            .arg(
+                Arg::with_name("fit-margin")
+                    .long("fit-margin")
+                    .value_name("FRACTION")
+                    .takes_value(true)
+                    .validator(validate_fit_margin)
+                    .help("How much of the window to leave around the scene when fitting it to \
+                           the window with F (default 0.05)"),
+            )
+            .arg(
+                Arg::with_name("lod")
+                    .long("lod")
+                    .help("Skip paths smaller than a pixel in 2D mode"),
+            )
+            .arg(
+                Arg::with_name("watch")
+                    .long("watch")
+                    .help("Reload the SVG file whenever it changes on disk"),
+            )
+            .arg(
+                Arg::with_name("stats-output")
+                    .long("stats-output")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .help("Append each frame's stats to PATH, one JSON object per line"),
+            )
+            .arg(
+                Arg::with_name("target-fps")
+                    .long("target-fps")
+                    .value_name("FPS")
+                    .takes_value(true)
+                    .validator(validate_target_fps)
+                    .help("Don't render more than FPS frames per second"),
+            )
+            .arg(
+                Arg::with_name("camera-state")
+                    .long("camera-state")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .help("Start with the camera state in the JSON file at PATH"),
+            )
+            .arg(
+                Arg::with_name("save-camera-state")
+                    .long("save-camera-state")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .help("Save the camera state to a JSON file at PATH on exit, for use with \
+                           --camera-state"),
+            )
+            .arg(
+                Arg::with_name("eye-offset-adjustment")
+                    .long("eye-offset-adjustment")
+                    .value_name("METRES")
+                    .takes_value(true)
+                    .allow_hyphen_values(true)
+                    .validator(validate_eye_offset_adjustment)
+                    .help("Move each eye of an immersive display outward by METRES (inward if \
+                           negative)"),
+            )
+            .arg(
+                Arg::with_name("up-axis")
+                    .long("up-axis")
+                    .takes_value(true)
+                    .possible_values(&["y", "z"])
+                    .help("The world axis that points up in 3D mode"),
+            )
+            .arg(
+                Arg::with_name("projection")
+                    .long("projection")
+                    .takes_value(true)
+                    .possible_values(&["perspective", "orthographic"])
+                    .help("How the camera projects the scene in 3D mode"),
+            )
+            .arg(
+                Arg::with_name("screenshot")
+                    .long("screenshot")
+                    .value_name("PATH")
+                    .takes_value(true)
+                    .help("Save a screenshot of the first frame to PATH (format from extension)"),
+            )
+            .arg(
+                Arg::with_name("screenshot-scale")
+                    .long("screenshot-scale")
+                    .value_name("FACTOR")
+                    .takes_value(true)
+                    .validator(validate_screenshot_scale)
+                    .help("Render raster screenshots at FACTOR times the window size"),
+            )
+            .arg(
+                Arg::with_name("headless")
+                    .long("headless")
+                    .requires("screenshot")
+                    .help("Render one frame offscreen, save the screenshot, and exit"),
             )
             .arg(
                 Arg::with_name("INPUT")
-                    .help("Path to the SVG file to render")
+                    .help("Path to the SVG file to render, or to a directory of SVG files to page \
+                           through with Page Up and Page Down")
                     .index(1),
             )
             .get_matches();
 
         if let Some(jobs) = matches.value_of("jobs") {
-            self.jobs = jobs.parse().ok();
+            self.jobs = Some(jobs.parse().unwrap());
         }
 
         if matches.is_present("3d") {
@@ -714,19 +1408,203 @@ impl Options {
         }
 
         if let Some(background_color) = matches.value_of("background") {
-            self.background_color = match background_color {
-                "light" => BackgroundColor::Light,
-                "dark" => BackgroundColor::Dark,
-                _ => BackgroundColor::Transparent,
+            self.background_color = parse_background_color(background_color).unwrap();
+        }
+
+        if let Some(ground_color) = matches.value_of("ground-color") {
+            self.ground_color = parse_hex_color(ground_color).unwrap();
+        }
+
+        if let Some(gridline_color) = matches.value_of("grid-color") {
+            self.gridline_color = parse_hex_color(gridline_color).unwrap();
+        }
+
+        if let Some(letterbox_color) = matches.value_of("letterbox-color") {
+            self.letterbox_color = Some(parse_hex_color(letterbox_color).unwrap());
+        }
+
+        if let Some(warmup_frames) = matches.value_of("warmup-frames") {
+            self.warmup_frames = warmup_frames.parse().unwrap();
+        }
+
+        if matches.is_present("step") {
+            self.single_step = true;
+        }
+
+        if let Some(zoom_step) = matches.value_of("zoom-step") {
+            self.zoom_step = zoom_step.parse().unwrap();
+        }
+
+        if let Some(zoom_speed) = matches.value_of("zoom-speed") {
+            self.zoom_speed = zoom_speed.parse().unwrap();
+        }
+
+        if let Some(fit_margin) = matches.value_of("fit-margin") {
+            self.fit_margin = fit_margin.parse().unwrap();
+        }
+
+        if let Some(eye_offset_adjustment) = matches.value_of("eye-offset-adjustment") {
+            self.eye_offset_adjustment = eye_offset_adjustment.parse().unwrap();
+        }
+
+        if let Some(mouse_speed) = matches.value_of("mouse-speed") {
+            self.mouse_speed = mouse_speed.parse().unwrap();
+        }
+
+        if let Some(move_speed) = matches.value_of("move-speed") {
+            self.move_speed = move_speed.parse().unwrap();
+        }
+
+        if matches.is_present("lod") {
+            self.lod = true;
+        }
+
+        if matches.is_present("watch") {
+            self.watch = true;
+        }
+
+        if let Some(stats_output) = matches.value_of("stats-output") {
+            self.stats_output = Some(PathBuf::from(stats_output));
+        }
+
+        if let Some(camera_state_path) = matches.value_of("camera-state") {
+            self.camera_state_path = Some(PathBuf::from(camera_state_path));
+        }
+
+        if let Some(save_camera_state_path) = matches.value_of("save-camera-state") {
+            self.save_camera_state_path = Some(PathBuf::from(save_camera_state_path));
+        }
+
+        if let Some(target_fps) = matches.value_of("target-fps") {
+            self.target_fps = Some(target_fps.parse().unwrap());
+        }
+
+        if let Some(projection) = matches.value_of("projection") {
+            self.projection = match projection {
+                "orthographic" => Projection::Orthographic,
+                _ => Projection::Perspective,
+            };
+        }
+
+        if let Some(up_axis) = matches.value_of("up-axis") {
+            self.up_axis = match up_axis {
+                "z" => UpAxis::Z,
+                _ => UpAxis::Y,
             };
         }
 
+        if let Some(screenshot_path) = matches.value_of("screenshot") {
+            self.screenshot_path = Some(PathBuf::from(screenshot_path));
+        }
+
+        if let Some(screenshot_scale) = matches.value_of("screenshot-scale") {
+            self.screenshot_scale = screenshot_scale.parse().unwrap();
+        }
+
+        if matches.is_present("headless") {
+            self.headless = true;
+        }
+
         if let Some(path) = matches.value_of("INPUT") {
             self.input_path = SVGPath::Path(PathBuf::from(path));
         };
     }
 }
 
+// Parses colors of the form `#rrggbb` or `#rrggbbaa`. The leading `#` is optional.
+fn parse_hex_color(string: &str) -> Result<ColorU, String> {
+    let hex = if string.starts_with('#') { &string[1..] } else { string };
+    if !hex.chars().all(|ch| ch.is_digit(16)) {
+        return Err(format!("`{}` is not a hex color", string));
+    }
+    match hex.len() {
+        6 => Ok(ColorU::from_u32(u32::from_str_radix(hex, 16).unwrap() << 8 | 0xff)),
+        8 => Ok(ColorU::from_u32(u32::from_str_radix(hex, 16).unwrap())),
+        _ => Err(format!("`{}` is not a hex color of the form #rrggbb or #rrggbbaa", string)),
+    }
+}
+
+fn validate_hex_color(string: String) -> Result<(), String> {
+    parse_hex_color(&string).map(|_| ())
+}
+
+fn parse_background_color(string: &str) -> Result<BackgroundColor, String> {
+    match string {
+        "light" => Ok(BackgroundColor::Light),
+        "dark" => Ok(BackgroundColor::Dark),
+        "transparent" => Ok(BackgroundColor::Transparent),
+        _ => parse_hex_color(string).map(BackgroundColor::Custom).map_err(|_| {
+            format!("`{}` is not `light`, `dark`, `transparent`, or a hex color", string)
+        }),
+    }
+}
+
+fn validate_background_color(string: String) -> Result<(), String> {
+    parse_background_color(&string).map(|_| ())
+}
+
+// Zooming out scales by `1 - step`, so the step has to stay below 1.
+fn validate_zoom_step(string: String) -> Result<(), String> {
+    match string.parse::<f32>() {
+        Ok(step) if step > 0.0 && step < 1.0 => Ok(()),
+        _ => Err(format!("`{}` is not a zoom step between 0 and 1", string)),
+    }
+}
+
+fn validate_zoom_speed(string: String) -> Result<(), String> {
+    match string.parse::<f32>() {
+        Ok(speed) if speed > 0.0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive zoom speed", string)),
+    }
+}
+
+// The margin is applied on both sides, so it has to stay below half of the window.
+fn validate_fit_margin(string: String) -> Result<(), String> {
+    match string.parse::<f32>() {
+        Ok(margin) if margin >= 0.0 && margin < 0.5 => Ok(()),
+        _ => Err(format!("`{}` is not a margin between 0 and 0.5", string)),
+    }
+}
+
+fn validate_jobs(string: String) -> Result<(), String> {
+    match string.parse::<usize>() {
+        Ok(jobs) if jobs > 0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive number of threads", string)),
+    }
+}
+
+fn validate_target_fps(string: String) -> Result<(), String> {
+    match string.parse::<u32>() {
+        Ok(fps) if fps > 0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive frame rate", string)),
+    }
+}
+
+fn validate_camera_speed(string: String) -> Result<(), String> {
+    match string.parse::<f32>() {
+        Ok(speed) if speed > 0.0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive camera speed", string)),
+    }
+}
+
+fn validate_eye_offset_adjustment(string: String) -> Result<(), String> {
+    match string.parse::<f32>() {
+        Ok(offset) if offset.is_finite() => Ok(()),
+        _ => Err(format!("`{}` is not a distance in metres", string)),
+    }
+}
+
+fn validate_frame_count(string: String) -> Result<(), String> {
+    string.parse::<u32>().map(|_| ()).map_err(|_| format!("`{}` is not a frame count", string))
+}
+
+fn validate_screenshot_scale(string: String) -> Result<(), String> {
+    match string.parse::<u32>() {
+        Ok(scale) if scale > 0 => Ok(()),
+        _ => Err(format!("`{}` is not a positive scale factor", string)),
+    }
+}
+
 #[derive(Clone, Copy, PartialEq)]
 pub enum UIVisibility {
     None,
@@ -734,18 +1612,137 @@ pub enum UIVisibility {
     All,
 }
 
-fn load_scene(resource_loader: &dyn ResourceLoader, input_path: &SVGPath) -> BuiltSVG {
-    let mut data;
-    match *input_path {
-        SVGPath::Default => data = resource_loader.slurp(DEFAULT_SVG_VIRTUAL_PATH).unwrap(),
-        SVGPath::Resource(ref name) => data = resource_loader.slurp(name).unwrap(),
-        SVGPath::Path(ref path) => {
-            data = vec![];
-            File::open(path).unwrap().read_to_end(&mut data).unwrap();
-        }
+fn load_scene(resource_loader: &dyn ResourceLoader, input_path: &SVGPath)
+              -> Result<BuiltSVG, LoadError> {
+    let data = match *input_path {
+        SVGPath::Default => load_default_svg(resource_loader)?,
+        SVGPath::Resource(ref name) => resource_loader.slurp(name)?,
+        SVGPath::Path(ref path) => fs::read(path)?,
     };
 
-    BuiltSVG::from_tree(Tree::from_data(&data, &UsvgOptions::default()).unwrap())
+    Ok(BuiltSVG::from_tree(Tree::from_data(&data, &UsvgOptions::default())?))
+}
+
+#[derive(Debug)]
+enum LoadError {
+    IO(io::Error),
+    Parse(usvg::Error),
+}
+
+impl Display for LoadError {
+    fn fmt(&self, formatter: &mut Formatter) -> fmt::Result {
+        match *self {
+            LoadError::IO(ref err) => write!(formatter, "{}", err),
+            LoadError::Parse(ref err) => write!(formatter, "not a valid SVG ({:?})", err),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(err: io::Error) -> LoadError {
+        LoadError::IO(err)
+    }
+}
+
+impl From<usvg::Error> for LoadError {
+    fn from(err: usvg::Error) -> LoadError {
+        LoadError::Parse(err)
+    }
+}
+
+fn load_camera_state(path: &Path) -> io::Result<CameraState> {
+    let file = File::open(path)?;
+    serde_json::from_reader(file).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn save_camera_state(path: &Path, camera_state: &CameraState) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    serde_json::to_writer_pretty(&mut file, camera_state)?;
+    writeln!(file)
+}
+
+// Returns the SVG files directly inside `directory`, sorted by name.
+fn list_svg_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        let is_svg = path.extension()
+                         .and_then(|extension| extension.to_str())
+                         .map_or(false, |extension| extension.eq_ignore_ascii_case("svg"));
+        if is_svg && path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+    Ok(paths)
+}
+
+fn describe_svg_path(svg_path: &SVGPath) -> String {
+    match *svg_path {
+        SVGPath::Default => "the default SVG".to_owned(),
+        SVGPath::Resource(ref name) => format!("`{}`", name),
+        SVGPath::Path(ref path) => format!("`{}`", path.display()),
+    }
+}
+
+fn load_default_svg(resource_loader: &dyn ResourceLoader) -> io::Result<Vec<u8>> {
+    if let Ok(name) = env::var(DEFAULT_SVG_ENV_VAR) {
+        match resource_loader.slurp(&name) {
+            Ok(data) => return Ok(data),
+            Err(err) => {
+                eprintln!("Warning: Couldn't load `{}` named by {} ({}); using the default SVG.",
+                          name,
+                          DEFAULT_SVG_ENV_VAR,
+                          err)
+            }
+        }
+    }
+    resource_loader.slurp(DEFAULT_SVG_VIRTUAL_PATH)
+}
+
+// One line of `--stats-output`. Times are in milliseconds; the GPU stage times are `null` when
+// the GPU hasn't reported them.
+#[derive(Serialize)]
+struct StatsRecord {
+    frame: u32,
+    path_count: usize,
+    fill_count: usize,
+    alpha_tile_count: usize,
+    solid_tile_count: usize,
+    build_time: f64,
+    stage_0_time: Option<f64>,
+    stage_1_time: Option<f64>,
+    present_time: f64,
+}
+
+impl StatsRecord {
+    fn new(frame: u32,
+           stats: &RenderStats,
+           build_time: Duration,
+           rendering_time: Option<RenderTime>,
+           present_time: Duration)
+           -> StatsRecord {
+        StatsRecord {
+            frame,
+            path_count: stats.path_count,
+            fill_count: stats.fill_count,
+            alpha_tile_count: stats.alpha_tile_count,
+            solid_tile_count: stats.solid_tile_count,
+            build_time: duration_to_ms(build_time),
+            stage_0_time: rendering_time.map(|time| duration_to_ms(time.stage_0)),
+            stage_1_time: rendering_time.map(|time| duration_to_ms(time.stage_1)),
+            present_time: duration_to_ms(present_time),
+        }
+    }
+}
+
+fn write_stats_line<W>(writer: &mut W, record: &StatsRecord) -> io::Result<()> where W: Write {
+    serde_json::to_writer(&mut *writer, record)?;
+    writeln!(writer)
+}
+
+fn duration_to_ms(time: Duration) -> f64 {
+    time.as_secs() as f64 * 1000.0 + time.subsec_nanos() as f64 / 1000000.0
 }
 
 fn center_of_window(window_size: &WindowSize) -> Vector2F {
@@ -753,12 +1750,12 @@ fn center_of_window(window_size: &WindowSize) -> Vector2F {
 }
 
 fn get_svg_building_message(built_svg: &BuiltSVG) -> String {
-    if built_svg.result_flags.is_empty() {
+    if built_svg.result_counts.is_empty() {
         return String::new();
     }
     format!(
         "Warning: These features in the SVG are unsupported: {}.",
-        built_svg.result_flags
+        built_svg.result_counts
     )
 }
 
@@ -783,6 +1780,27 @@ fn emit_message<W>(
     });
 }
 
+// Moves the first eye left and the others right by `offset`, in the same way that `Camera::new()`
+// separates the eyes in VR mode.
+fn adjust_eye_offsets(eye_transforms: &mut [OcularTransform], offset: f32) {
+    for (index, eye_transform) in eye_transforms.iter_mut().enumerate() {
+        let this_eye_offset = if index == 0 { offset } else { -offset };
+        eye_transform.modelview_to_eye = eye_transform
+            .modelview_to_eye
+            .pre_mul(&Transform3DF::from_translation(this_eye_offset, 0.0, 0.0));
+    }
+}
+
+// Zeroes stick deflections within the dead zone and rescales the rest so that movement starts
+// smoothly from zero at its edge.
+fn apply_gamepad_dead_zone(value: f32) -> f32 {
+    let magnitude = value.abs();
+    if magnitude < GAMEPAD_DEAD_ZONE {
+        return 0.0;
+    }
+    value.signum() * (magnitude.min(1.0) - GAMEPAD_DEAD_ZONE) / (1.0 - GAMEPAD_DEAD_ZONE)
+}
+
 struct Frame {
     transform: RenderTransform,
     ui_events: Vec<UIEvent>,
@@ -803,9 +1821,10 @@ impl Frame {
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum BackgroundColor {
-    Light = 0,
-    Dark = 1,
-    Transparent = 2,
+    Light,
+    Dark,
+    Transparent,
+    Custom(ColorU),
 }
 
 impl BackgroundColor {
@@ -814,6 +1833,7 @@ impl BackgroundColor {
             BackgroundColor::Light => "Light",
             BackgroundColor::Dark => "Dark",
             BackgroundColor::Transparent => "Transparent",
+            BackgroundColor::Custom(_) => "Custom",
         }
     }
 }
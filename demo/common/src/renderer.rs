@@ -11,33 +11,28 @@
 //! Rendering functionality for the demo.
 
 use crate::camera::{Camera, Mode};
+use crate::ui::ScreenshotFormat;
 use crate::window::{View, Window};
-use crate::{BackgroundColor, DemoApp, UIVisibility};
+use crate::{DemoApp, UIVisibility, emit_message};
 use image::ColorType;
+use image::bmp::BMPEncoder;
+use image::jpeg::JPEGEncoder;
+use image::png::PNGEncoder;
+use pathfinder_geometry::basic::rect::{RectF, RectI};
+use pathfinder_geometry::basic::vector::{Vector2F, Vector2I};
 use pathfinder_geometry::color::{ColorF, ColorU};
-use pathfinder_gpu::{ClearParams, DepthFunc, DepthState, Device, Primitive, RenderState};
+use pathfinder_gl::GLDevice;
+use pathfinder_gpu::{ClearParams, DepthState, Device, Primitive, RenderState};
 use pathfinder_gpu::{TextureFormat, UniformData};
-use pathfinder_geometry::basic::transform3d::Transform3DF;
+use pathfinder_geometry::basic::transform3d::{Perspective, Transform3DF};
 use pathfinder_renderer::gpu::renderer::{DestFramebuffer, RenderMode};
 use pathfinder_renderer::gpu_data::RenderCommand;
 use pathfinder_renderer::options::RenderTransform;
 use pathfinder_renderer::post::DEFRINGING_KERNEL_CORE_GRAPHICS;
+use std::fs::File;
+use std::io::BufWriter;
 use std::path::PathBuf;
 
-const GROUND_SOLID_COLOR: ColorU = ColorU {
-    r: 80,
-    g: 80,
-    b: 80,
-    a: 255,
-};
-
-const GROUND_LINE_COLOR: ColorU = ColorU {
-    r: 127,
-    g: 127,
-    b: 127,
-    a: 255,
-};
-
 const GRIDLINE_COUNT: i32 = 10;
 
 impl<W> DemoApp<W> where W: Window {
@@ -51,27 +46,15 @@ impl<W> DemoApp<W> where W: Window {
         let scene_count = match self.camera.mode() {
             Mode::VR => {
                 let viewport = self.window.viewport(View::Stereo(0));
-                if self.scene_framebuffer.is_none()
-                    || self.renderer.device.texture_size(
-                        &self
-                            .renderer
-                            .device
-                            .framebuffer_texture(self.scene_framebuffer.as_ref().unwrap()),
-                    ) != viewport.size()
-                {
-                    let scene_texture = self
-                        .renderer
-                        .device
-                        .create_texture(TextureFormat::RGBA8, viewport.size());
-                    self.scene_framebuffer =
-                        Some(self.renderer.device.create_framebuffer(scene_texture));
-                }
-                self.renderer
-                    .replace_dest_framebuffer(DestFramebuffer::Other(
-                        self.scene_framebuffer.take().unwrap(),
-                    ));
+                let scene_framebuffer = self.take_scene_framebuffer(viewport.size());
+                self.renderer.replace_dest_framebuffer(DestFramebuffer::Other(scene_framebuffer));
                 2
             }
+            _ if self.options.headless => {
+                let scene_framebuffer = self.take_scene_framebuffer(window_size);
+                self.renderer.replace_dest_framebuffer(DestFramebuffer::Other(scene_framebuffer));
+                1
+            }
             _ => {
                 self.renderer
                     .replace_dest_framebuffer(DestFramebuffer::Default {
@@ -86,10 +69,11 @@ impl<W> DemoApp<W> where W: Window {
         self.renderer.bind_dest_framebuffer();
 
         // Clear to the appropriate color.
-        let clear_color = if scene_count == 2 {
-            ColorF::transparent_black()
-        } else {
-            self.background_color().to_f32()
+        let content_rect = self.letterbox_content_rect();
+        let clear_color = match (scene_count, content_rect, self.options.letterbox_color) {
+            (2, _, _) => ColorF::transparent_black(),
+            (_, Some(_), Some(letterbox_color)) => letterbox_color.to_f32(),
+            _ => self.background_color().to_f32(),
         };
         self.renderer.device.clear(&ClearParams {
             color: Some(clear_color),
@@ -98,9 +82,48 @@ impl<W> DemoApp<W> where W: Window {
             ..ClearParams::default()
         });
 
+        // Clear the area covered by the scene to the background color, leaving the matte around it.
+        if let Some(content_rect) = content_rect {
+            self.renderer.device.clear(&ClearParams {
+                color: Some(self.background_color().to_f32()),
+                rect: Some(content_rect),
+                ..ClearParams::default()
+            });
+        }
+
         scene_count
     }
 
+    // Returns the offscreen scene framebuffer, reallocating it if it isn't the given size.
+    fn take_scene_framebuffer(&mut self, size: Vector2I) -> <GLDevice as Device>::Framebuffer {
+        let device = &self.renderer.device;
+        match self.scene_framebuffer.take() {
+            Some(framebuffer)
+                if device.texture_size(device.framebuffer_texture(&framebuffer)) == size => {
+                framebuffer
+            }
+            _ => device.create_framebuffer(device.create_texture(TextureFormat::RGBA8, size)),
+        }
+    }
+
+    // Returns the part of the viewport, in framebuffer coordinates, that the view box occupies in
+    // 2D mode when a letterbox color is set.
+    fn letterbox_content_rect(&self) -> Option<RectI> {
+        let transform = match self.camera {
+            Camera::TwoD(ref transform) if self.options.letterbox_color.is_some() => transform,
+            _ => return None,
+        };
+
+        let viewport = self.window.viewport(View::Mono);
+        let content_rect = transform.transform_rect(&self.scene_metadata.view_box);
+
+        // Flip to the framebuffer's bottom-left origin.
+        let origin = Vector2F::new(content_rect.min_x(),
+                                   viewport.size().y() as f32 - content_rect.max_y());
+        let content_rect = RectF::new(origin + viewport.origin().to_f32(), content_rect.size());
+        Some(content_rect.intersection(viewport.to_f32())?.round_out().to_i32())
+    }
+
     pub fn draw_scene(&mut self) {
         let view = self.ui_model.mode.view(0);
         self.window.make_current(view);
@@ -215,10 +238,13 @@ impl<W> DemoApp<W> where W: Window {
             RenderTransform::Perspective(perspective) => perspective,
         };
 
-        if self.ui_model.background_color == BackgroundColor::Transparent {
+        if self.background_color().is_fully_transparent() {
             return;
         }
 
+        // The ground is placed in SVG coordinates, under the bottom edge of the view box. The
+        // camera transform maps SVG coordinates to the world for the configured up axis, so this
+        // is the same under either one.
         let ground_scale = self.scene_metadata.view_box.max_x() * 2.0;
 
         let mut base_transform = perspective.transform;
@@ -242,11 +268,11 @@ impl<W> DemoApp<W> where W: Window {
         );
         device.set_uniform(
             &self.ground_program.ground_color_uniform,
-            UniformData::Vec4(GROUND_SOLID_COLOR.to_f32().0),
+            UniformData::Vec4(self.options.ground_color.to_f32().0),
         );
         device.set_uniform(
             &self.ground_program.gridline_color_uniform,
-            UniformData::Vec4(GROUND_LINE_COLOR.to_f32().0),
+            UniformData::Vec4(self.options.gridline_color.to_f32().0),
         );
         device.set_uniform(&self.ground_program.gridline_count_uniform,
                            UniformData::Int(GRIDLINE_COUNT));
@@ -254,7 +280,9 @@ impl<W> DemoApp<W> where W: Window {
             Primitive::Triangles,
             6,
             &RenderState {
-                depth: Some(DepthState { func: DepthFunc::Less, write: true }),
+                depth: self.ui_model.mode.depth_func().map(|func| {
+                    DepthState { func, write: true }
+                }),
                 ..RenderState::default()
             },
         );
@@ -278,21 +306,39 @@ impl<W> DemoApp<W> where W: Window {
             }
         }
 
-        if self.ui_model.mode == Mode::TwoD {
-            self.renderer.disable_depth();
-        } else {
-            self.renderer.enable_depth();
+        match self.ui_model.mode.depth_func() {
+            None => self.renderer.disable_depth(),
+            Some(depth_func) => {
+                self.renderer.enable_depth();
+                self.renderer.set_depth_func(depth_func);
+            }
         }
 
         self.renderer.begin_scene();
 
-        // Issue render commands!
-        for command in self.render_command_stream.as_mut().unwrap() {
-            self.renderer.render_command(&command);
-
-            if let RenderCommand::Finish { build_time } = command {
-                self.build_time = Some(build_time);
+        // Collect the render commands before issuing any, so that a scene that fails to build
+        // partway through never reaches the screen. The demo reads each stream to the end before
+        // starting the next build, so its streams are never superseded.
+        let render_command_stream = self.render_command_stream.as_mut().unwrap();
+        let render_commands: Vec<_> = render_command_stream.by_ref().collect();
+        if !render_command_stream.failed() {
+            for command in &render_commands {
+                if let RenderCommand::Finish { build_time } = *command {
+                    self.build_time = Some(build_time);
+                }
             }
+            self.last_render_commands = render_commands;
+        } else {
+            let message = "Warning: The scene failed to build; showing the last good frame.";
+            emit_message::<W>(&mut self.ui_model,
+                              &mut self.message_epoch,
+                              self.expire_message_event_id,
+                              message.to_owned());
+        }
+
+        // Issue render commands!
+        for command in &self.last_render_commands {
+            self.renderer.render_command(command);
         }
 
         self.current_frame
@@ -303,20 +349,94 @@ impl<W> DemoApp<W> where W: Window {
         self.renderer.end_scene();
     }
 
-    pub fn take_raster_screenshot(&mut self, path: PathBuf) {
-        let drawable_size = self.window_size.device_size();
-        let pixels = self
-            .renderer
-            .device
-            .read_pixels_from_default_framebuffer(drawable_size);
-        image::save_buffer(
-            path,
-            &pixels,
-            drawable_size.x() as u32,
-            drawable_size.y() as u32,
-            ColorType::RGBA(8),
-        )
-        .unwrap();
+    pub fn take_raster_screenshot(&mut self, path: PathBuf, format: ScreenshotFormat, scale: u32) {
+        let drawable_size = self.window_size.device_size().scale(scale as i32);
+        let mut pixels = if scale > 1 {
+            self.render_supersampled_scene(scale)
+        } else {
+            let device = &self.renderer.device;
+            match *self.renderer.dest_framebuffer() {
+                DestFramebuffer::Other(ref framebuffer) => {
+                    device.read_pixels_from_framebuffer(framebuffer, drawable_size)
+                }
+                DestFramebuffer::Default { .. } => {
+                    device.read_pixels_from_default_framebuffer(drawable_size)
+                }
+            }
+        };
+
+        let mut color_type = ColorType::RGBA(8);
+        if format.is_opaque() {
+            pixels = flatten_alpha(&pixels, self.background_color());
+            color_type = ColorType::RGB(8);
+        }
+
+        let (width, height) = (drawable_size.x() as u32, drawable_size.y() as u32);
+        let mut writer = BufWriter::new(File::create(path).unwrap());
+        match format {
+            ScreenshotFormat::PNG => {
+                PNGEncoder::new(writer).encode(&pixels, width, height, color_type).unwrap()
+            }
+            ScreenshotFormat::JPEG { quality } => {
+                JPEGEncoder::new_with_quality(&mut writer, quality)
+                    .encode(&pixels, width, height, color_type)
+                    .unwrap()
+            }
+            ScreenshotFormat::BMP => {
+                BMPEncoder::new(&mut writer).encode(&pixels, width, height, color_type).unwrap()
+            }
+        }
+    }
+
+    // Rebuilds the vector scene with the camera scaled up by `scale`, renders it into an offscreen
+    // framebuffer that many times the size of the window, and returns the resulting pixels. The
+    // 3D environment and the UI are left out.
+    fn render_supersampled_scene(&mut self, scale: u32) -> Vec<u8> {
+        let size = self.window_size.device_size().scale(scale as i32);
+        let transform = match self.render_transform.clone().unwrap() {
+            RenderTransform::Transform2D(transform) => {
+                RenderTransform::Transform2D(transform.post_scale(Vector2F::splat(scale as f32)))
+            }
+            RenderTransform::Perspective(perspective) => {
+                let window_size = perspective.window_size.scale(scale as i32);
+                RenderTransform::Perspective(Perspective::new(&perspective.transform, window_size))
+            }
+        };
+
+        // Build the scene at the larger size, then put the view box back for subsequent frames.
+        let view_box_size = self.window.viewport(self.ui_model.mode.view(0)).size();
+        let scaled_view_box_size = view_box_size.scale(scale as i32);
+        self.scene_proxy.set_view_box(RectF::new(Vector2F::default(),
+                                                 scaled_view_box_size.to_f32()));
+        let render_options = self.render_options(transform, scale as f32);
+        let render_command_stream = self.scene_proxy.build_with_stream(render_options);
+        self.scene_proxy.set_view_box(RectF::new(Vector2F::default(), view_box_size.to_f32()));
+
+        let texture = self.renderer.device.create_texture(TextureFormat::RGBA8, size);
+        let framebuffer = self.renderer.device.create_framebuffer(texture);
+        let old_dest_framebuffer =
+            self.renderer.replace_dest_framebuffer(DestFramebuffer::Other(framebuffer));
+
+        self.renderer.bind_dest_framebuffer();
+        self.renderer.device.clear(&ClearParams {
+            color: Some(self.background_color().to_f32()),
+            depth: Some(1.0),
+            stencil: Some(0),
+            ..ClearParams::default()
+        });
+
+        self.renderer.begin_scene();
+        for command in render_command_stream {
+            self.renderer.render_command(&command);
+        }
+        self.renderer.end_scene();
+
+        match self.renderer.replace_dest_framebuffer(old_dest_framebuffer) {
+            DestFramebuffer::Other(framebuffer) => {
+                self.renderer.device.read_pixels_from_framebuffer(&framebuffer, size)
+            }
+            DestFramebuffer::Default { .. } => unreachable!(),
+        }
     }
 
     pub fn draw_debug_ui(&mut self) {
@@ -334,3 +454,17 @@ impl<W> DemoApp<W> where W: Window {
         self.renderer.draw_debug_ui();
     }
 }
+
+// Composites premultiplied RGBA pixels over an opaque background, producing RGB pixels.
+fn flatten_alpha(pixels: &[u8], background: ColorU) -> Vec<u8> {
+    let background = [background.r as u32, background.g as u32, background.b as u32];
+    let mut flattened = Vec::with_capacity(pixels.len() / 4 * 3);
+    for pixel in pixels.chunks(4) {
+        let inv_alpha = 255 - pixel[3] as u32;
+        for channel in 0..3 {
+            let value = pixel[channel] as u32 + (background[channel] * inv_alpha + 127) / 255;
+            flattened.push(value.min(255) as u8);
+        }
+    }
+    flattened
+}
@@ -19,7 +19,7 @@ use pathfinder_renderer::gpu::debug::DebugUIPresenter;
 use pathfinder_ui::{BUTTON_HEIGHT, BUTTON_TEXT_OFFSET, BUTTON_WIDTH, FONT_ASCENT, PADDING};
 use pathfinder_ui::{TEXT_COLOR, TOOLTIP_HEIGHT, WINDOW_COLOR};
 use std::f32::consts::PI;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 const SLIDER_WIDTH: i32 = 360;
 const SLIDER_HEIGHT: i32 = 48;
@@ -34,11 +34,13 @@ const BACKGROUND_PANEL_WIDTH: i32 = 250;
 const BACKGROUND_PANEL_HEIGHT: i32 = BUTTON_HEIGHT * 3;
 
 const SCREENSHOT_PANEL_WIDTH: i32 = 275;
-const SCREENSHOT_PANEL_HEIGHT: i32 = BUTTON_HEIGHT * 2;
+const SCREENSHOT_PANEL_HEIGHT: i32 = BUTTON_HEIGHT * 4;
 
 const ROTATE_PANEL_WIDTH: i32 = SLIDER_WIDTH + PADDING * 2;
 const ROTATE_PANEL_HEIGHT: i32 = PADDING * 2 + SLIDER_HEIGHT;
 
+pub const DEFAULT_JPEG_QUALITY: u8 = 90;
+
 static EFFECTS_PNG_NAME: &'static str = "demo-effects";
 static OPEN_PNG_NAME: &'static str = "demo-open";
 static ROTATE_PNG_NAME: &'static str = "demo-rotate";
@@ -56,6 +58,7 @@ pub struct DemoUIModel {
     pub subpixel_aa_effect_enabled: bool,
     pub rotation: i32,
     pub message: String,
+    pub screenshot_scale: u32,
 }
 
 impl DemoUIModel {
@@ -68,6 +71,7 @@ impl DemoUIModel {
             subpixel_aa_effect_enabled: false,
             rotation: SLIDER_WIDTH / 2,
             message: String::new(),
+            screenshot_scale: options.screenshot_scale,
         }
     }
 
@@ -199,7 +203,12 @@ where
         }
 
         // Draw screenshot panel, if necessary.
-        self.draw_screenshot_panel(device, window, debug_ui_presenter, position.x(), action);
+        self.draw_screenshot_panel(device,
+                                   window,
+                                   debug_ui_presenter,
+                                   position.x(),
+                                   model.screenshot_scale,
+                                   action);
         position += Vector2I::new(button_size.x() + PADDING, 0);
 
         // Draw mode switch.
@@ -372,6 +381,7 @@ where
         window: &mut W,
         debug_ui_presenter: &mut DebugUIPresenter<D>,
         panel_x: i32,
+        scale: u32,
         action: &mut UIAction,
     ) where W: Window {
         if !self.screenshot_panel_visible {
@@ -390,22 +400,24 @@ where
             WINDOW_COLOR,
         );
 
-        self.draw_screenshot_menu_item(
-            device,
-            window,
-            debug_ui_presenter,
-            ScreenshotType::PNG,
-            panel_position,
-            action,
-        );
-        self.draw_screenshot_menu_item(
-            device,
-            window,
-            debug_ui_presenter,
+        let screenshot_types = [
+            ScreenshotType::Raster(ScreenshotFormat::PNG),
+            ScreenshotType::Raster(ScreenshotFormat::JPEG { quality: DEFAULT_JPEG_QUALITY }),
+            ScreenshotType::Raster(ScreenshotFormat::BMP),
             ScreenshotType::SVG,
-            panel_position,
-            action,
-        );
+        ];
+        for (index, &screenshot_type) in screenshot_types.iter().enumerate() {
+            self.draw_screenshot_menu_item(
+                device,
+                window,
+                debug_ui_presenter,
+                screenshot_type,
+                index as i32,
+                scale,
+                panel_position,
+                action,
+            );
+        }
     }
 
     fn draw_background_panel(
@@ -432,30 +444,20 @@ where
             WINDOW_COLOR,
         );
 
-        self.draw_background_menu_item(
-            device,
-            debug_ui_presenter,
-            BackgroundColor::Light,
-            panel_position,
-            action,
-            model,
-        );
-        self.draw_background_menu_item(
-            device,
-            debug_ui_presenter,
-            BackgroundColor::Dark,
-            panel_position,
-            action,
-            model,
-        );
-        self.draw_background_menu_item(
-            device,
-            debug_ui_presenter,
-            BackgroundColor::Transparent,
-            panel_position,
-            action,
-            model,
-        );
+        // A custom color from the command line isn't offered here; picking one of these
+        // replaces it.
+        let colors = [BackgroundColor::Light, BackgroundColor::Dark, BackgroundColor::Transparent];
+        for (index, &color) in colors.iter().enumerate() {
+            self.draw_background_menu_item(
+                device,
+                debug_ui_presenter,
+                color,
+                index as i32,
+                panel_position,
+                action,
+                model,
+            );
+        }
     }
 
     fn draw_rotate_panel(
@@ -518,11 +520,17 @@ where
         window: &mut W,
         debug_ui_presenter: &mut DebugUIPresenter<D>,
         screenshot_type: ScreenshotType,
+        index: i32,
+        scale: u32,
         panel_position: Vector2I,
         action: &mut UIAction,
     ) where W: Window {
-        let index = screenshot_type as i32;
-        let text = format!("Save as {}...", screenshot_type.as_str());
+        let text = match screenshot_type {
+            ScreenshotType::Raster(_) if scale > 1 => {
+                format!("Save as {} ({}x)...", screenshot_type.as_str(), scale)
+            }
+            _ => format!("Save as {}...", screenshot_type.as_str()),
+        };
 
         let widget_size = Vector2I::new(BACKGROUND_PANEL_WIDTH, BUTTON_HEIGHT);
         let widget_origin = panel_position + Vector2I::new(0, widget_size.y() * index);
@@ -533,7 +541,11 @@ where
             // the contents of the file.
             if let Ok(path) = window.run_save_dialog(screenshot_type.extension()) {
                 self.screenshot_panel_visible = false;
-                *action = UIAction::TakeScreenshot(ScreenshotInfo { kind: screenshot_type, path });
+                *action = UIAction::TakeScreenshot(ScreenshotInfo {
+                    kind: screenshot_type,
+                    path,
+                    scale,
+                });
             }
         }
     }
@@ -543,11 +555,12 @@ where
         device: &D,
         debug_ui_presenter: &mut DebugUIPresenter<D>,
         color: BackgroundColor,
+        index: i32,
         panel_position: Vector2I,
         action: &mut UIAction,
         model: &mut DemoUIModel,
     ) {
-        let (text, index) = (color.as_str(), color as i32);
+        let text = color.as_str();
 
         let widget_size = Vector2I::new(BACKGROUND_PANEL_WIDTH, BUTTON_HEIGHT);
         let widget_origin = panel_position + Vector2I::new(0, widget_size.y() * index);
@@ -616,6 +629,7 @@ pub enum UIAction {
     TakeScreenshot(ScreenshotInfo),
     ZoomIn,
     ZoomActualSize,
+    FitToWindow,
     ZoomOut,
     Rotate(f32),
 }
@@ -624,26 +638,63 @@ pub enum UIAction {
 pub struct ScreenshotInfo {
     pub kind: ScreenshotType,
     pub path: PathBuf,
+    // Raster screenshots are rendered at this multiple of the window's device size.
+    pub scale: u32,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum ScreenshotType {
-    PNG = 0,
-    SVG = 1,
+    Raster(ScreenshotFormat),
+    SVG,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScreenshotFormat {
+    PNG,
+    JPEG { quality: u8 },
+    BMP,
 }
 
 impl ScreenshotType {
+    // Guesses the screenshot type from the extension of `path`, defaulting to PNG.
+    pub fn from_path(path: &Path) -> ScreenshotType {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        match extension.map(|extension| extension.to_lowercase()).as_ref().map(|s| &**s) {
+            Some("jpg") | Some("jpeg") => {
+                ScreenshotType::Raster(ScreenshotFormat::JPEG { quality: DEFAULT_JPEG_QUALITY })
+            }
+            Some("bmp") => ScreenshotType::Raster(ScreenshotFormat::BMP),
+            Some("svg") => ScreenshotType::SVG,
+            _ => ScreenshotType::Raster(ScreenshotFormat::PNG),
+        }
+    }
+
     fn extension(&self) -> &'static str {
         match *self {
-            ScreenshotType::PNG => "png",
+            ScreenshotType::Raster(ScreenshotFormat::PNG) => "png",
+            ScreenshotType::Raster(ScreenshotFormat::JPEG { .. }) => "jpg",
+            ScreenshotType::Raster(ScreenshotFormat::BMP) => "bmp",
             ScreenshotType::SVG => "svg",
         }
     }
 
     fn as_str(&self) -> &'static str {
         match *self {
-            ScreenshotType::PNG => "PNG",
+            ScreenshotType::Raster(ScreenshotFormat::PNG) => "PNG",
+            ScreenshotType::Raster(ScreenshotFormat::JPEG { .. }) => "JPEG",
+            ScreenshotType::Raster(ScreenshotFormat::BMP) => "BMP",
             ScreenshotType::SVG => "SVG",
         }
     }
 }
+
+impl ScreenshotFormat {
+    // Whether the format can't store an alpha channel, so pixels must be flattened first.
+    #[inline]
+    pub fn is_opaque(&self) -> bool {
+        match *self {
+            ScreenshotFormat::JPEG { .. } => true,
+            ScreenshotFormat::PNG | ScreenshotFormat::BMP => false,
+        }
+    }
+}
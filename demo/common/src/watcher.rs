@@ -0,0 +1,102 @@
+// pathfinder/demo/common/src/watcher.rs
+//
+// Copyright © 2019 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Watches the SVG file being displayed so that it can be reloaded when it changes on disk.
+
+use crate::window::Window;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+const POLL_INTERVAL_MS: u64 = 250;
+
+// Editors often save a file in several writes, so wait for it to stop changing for this long
+// before reporting it.
+const DEBOUNCE_INTERVAL_MS: u64 = 100;
+
+#[derive(Default)]
+pub struct SVGWatcher {
+    path: Option<PathBuf>,
+    generation: Arc<AtomicUsize>,
+}
+
+impl SVGWatcher {
+    /// Starts watching `path`, replacing any file that was previously being watched.
+    ///
+    /// Whenever the file changes, a user event of type `event_id` is pushed to the window, with
+    /// the watcher generation as its data. Pass that to `is_current()` to ignore events from
+    /// files that are no longer being watched.
+    pub fn watch<W>(&mut self, path: PathBuf, event_id: u32) where W: Window {
+        self.unwatch();
+        self.path = Some(path.clone());
+
+        let generation = self.generation.clone();
+        let current_generation = generation.load(Ordering::SeqCst);
+        thread::spawn(move || {
+            let mut last_modified = modified_time(&path);
+            loop {
+                thread::sleep(Duration::from_millis(POLL_INTERVAL_MS));
+                if generation.load(Ordering::SeqCst) != current_generation {
+                    return;
+                }
+
+                let mut modified = modified_time(&path);
+                if modified == last_modified {
+                    continue;
+                }
+                loop {
+                    thread::sleep(Duration::from_millis(DEBOUNCE_INTERVAL_MS));
+                    let new_modified = modified_time(&path);
+                    if new_modified == modified {
+                        break;
+                    }
+                    modified = new_modified;
+                }
+                last_modified = modified;
+
+                if generation.load(Ordering::SeqCst) != current_generation {
+                    return;
+                }
+                W::push_user_event(event_id, current_generation as u32);
+            }
+        });
+    }
+
+    /// Stops watching the current file, if any.
+    pub fn unwatch(&mut self) {
+        self.path = None;
+        self.generation.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    #[inline]
+    pub fn is_current(&self, generation: u32) -> bool {
+        self.path.is_some() && self.generation.load(Ordering::SeqCst) as u32 == generation
+    }
+}
+
+impl Drop for SVGWatcher {
+    fn drop(&mut self) {
+        self.unwatch();
+    }
+}
+
+// A missing or unreadable file reports `None`, so deleting and recreating the file (as some
+// editors do on save) counts as a change.
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
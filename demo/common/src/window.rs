@@ -48,11 +48,22 @@ pub enum Event {
     MouseMoved(Vector2I),
     MouseDragged(Vector2I),
     Zoom(f32, Vector2I),
+    Scroll(f32, Vector2I),
     Look {
         pitch: f32,
         yaw: f32,
     },
     SetEyeTransforms(Vec<OcularTransform>),
+    /// A gamepad stick moved. `value` ranges from -1 to 1, with negative values to the left and
+    /// up.
+    GamepadAxis {
+        axis: GamepadAxis,
+        value: f32,
+    },
+    GamepadButton {
+        button: GamepadButton,
+        pressed: bool,
+    },
     OpenSVG(SVGPath),
     User {
         message_type: u32,
@@ -64,7 +75,28 @@ pub enum Event {
 pub enum Keycode {
     Alphanumeric(u8),
     Escape,
+    Space,
     Tab,
+    PageUp,
+    PageDown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadAxis {
+    LeftX,
+    LeftY,
+    RightX,
+    RightY,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GamepadButton {
+    A,
+    B,
+    X,
+    Y,
+    Back,
+    Start,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -91,7 +91,8 @@ pub extern "C" fn magicleap_pathfinder_demo_init(egl_display: EGLDisplay, egl_co
     options.background_color = BackgroundColor::Transparent;
     options.mode = Mode::VR;
     options.jobs = Some(3);
-    
+    options.command_line_overrides();
+
     let demo = DemoApp::new(window, window_size, options);
     info!("Initialized app");
 
@@ -223,6 +224,7 @@ pub unsafe extern "C" fn magicleap_pathfinder_render(pf: *mut c_void, options: *
             transform: RenderTransform::Transform2D(transform),
             dilation: Vector2F::default(),
             subpixel_aa_enabled: false,
+            lod_threshold: 0.0,
         };
 
         let scene_proxy = SceneProxy::from_scene(svg.scene.clone(), SequentialExecutor);
@@ -24,6 +24,7 @@ use crate::c_api::MLLogLevel;
 use crate::c_api::MLLoggingLog;
 use crate::c_api::MLMat4f;
 use crate::c_api::MLQuaternionf;
+use crate::c_api::MLResult;
 use crate::c_api::MLRectf;
 use crate::c_api::MLTransform;
 use crate::c_api::MLVec3f;
@@ -41,6 +42,7 @@ use gl::types::GLuint;
 
 use log;
 use log::debug;
+use log::error;
 use log::info;
 
 use pathfinder_demo::window::Event;
@@ -71,6 +73,10 @@ use std::os::raw::c_void;
 use std::path::PathBuf;
 use std::thread;
 use std::time::Duration;
+use std::time::Instant;
+
+// How long to keep retrying a frame that the compositor times out on before giving up.
+const DEFAULT_MAX_FRAME_TIMEOUT_SECS: u64 = 30;
 
 pub struct MagicLeapWindow {
     framebuffer_id: GLuint,
@@ -83,6 +89,7 @@ pub struct MagicLeapWindow {
     pose_event: Option<Vec<OcularTransform>>,
     running: bool,
     in_frame: bool,
+    max_frame_timeout: Duration,
 }
 
 impl Window for MagicLeapWindow {
@@ -121,7 +128,11 @@ impl Window for MagicLeapWindow {
     }
 
     fn make_current(&mut self, view: View) {
-        self.begin_frame();
+        if let Err(err) = self.begin_frame() {
+            error!("PF couldn't begin frame, shutting down: {:?}", err);
+            self.running = false;
+            return;
+        }
         let eye = match view {
             View::Stereo(eye) if (eye as usize) < ML_VIRTUAL_CAMERA_COUNT => eye as usize,
             _ => { debug!("Asked for unexpected view: {:?}", view); 0 }
@@ -143,7 +154,10 @@ impl Window for MagicLeapWindow {
 
     fn present(&mut self) {
         self.end_frame();
-        self.begin_frame();
+        if let Err(err) = self.begin_frame() {
+            error!("PF couldn't begin frame, shutting down: {:?}", err);
+            self.running = false;
+        }
     }
 }
 
@@ -192,9 +206,16 @@ impl MagicLeapWindow {
             pose_event: None,
             running: true,
             in_frame: false,
+            max_frame_timeout: Duration::from_secs(DEFAULT_MAX_FRAME_TIMEOUT_SECS),
         }
     }
 
+    /// Sets how long beginning a frame may keep retrying after the compositor times out before
+    /// the window gives up and stops running.
+    pub fn set_max_frame_timeout(&mut self, max_frame_timeout: Duration) {
+        self.max_frame_timeout = max_frame_timeout;
+    }
+
     pub fn size(&self) -> WindowSize {
         WindowSize {
             logical_size: self.size,
@@ -210,7 +231,9 @@ impl MagicLeapWindow {
         self.pose_event.take().map(Event::SetEyeTransforms)
     }
 
-    fn begin_frame(&mut self) {
+    // Returns `ML_RESULT_TIMEOUT` if the compositor is still timing out after
+    // `max_frame_timeout`.
+    fn begin_frame(&mut self) -> Result<(), MLResult> {
         if !self.in_frame {
             debug!("PF beginning frame");
             let mut params = unsafe { mem::zeroed() };
@@ -220,17 +243,22 @@ impl MagicLeapWindow {
                 let mut result = MLGraphicsBeginFrame(self.graphics_client, &params, &mut self.frame_handle, &mut self.virtual_camera_array);
                 if result == ML_RESULT_TIMEOUT {
                     info!("PF frame timeout");
+                    let start = Instant::now();
                     let mut sleep = Duration::from_millis(1);
                     let max_sleep = Duration::from_secs(5);
                     while result == ML_RESULT_TIMEOUT {
-                        sleep = (sleep * 2).min(max_sleep);
+                        let remaining = match self.max_frame_timeout.checked_sub(start.elapsed()) {
+                            Some(remaining) if remaining > Duration::default() => remaining,
+                            _ => return Err(ML_RESULT_TIMEOUT),
+                        };
+                        sleep = (sleep * 2).min(max_sleep).min(remaining);
                         info!("PF exponential backoff {}ms", sleep.as_millis());
                         thread::sleep(sleep);
                         result = MLGraphicsBeginFrame(self.graphics_client, &params, &mut self.frame_handle, &mut self.virtual_camera_array);
                     }
                     info!("PF frame finished timeout");
                 }
-                result.unwrap();
+                result.ok()?;
             }
             let virtual_camera_array = &self.virtual_camera_array;
             let initial_camera = self.initial_camera_transform.get_or_insert_with(|| {
@@ -256,6 +284,7 @@ impl MagicLeapWindow {
             self.pose_event = Some(camera_transforms);
             debug!("PF begun frame");
         }
+        Ok(())
     }
 
     fn end_frame(&mut self) {
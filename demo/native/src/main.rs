@@ -11,16 +11,19 @@
 //! A demo app for Pathfinder using SDL 2.
 
 use nfd::Response;
-use pathfinder_demo::window::{Event, Keycode, SVGPath, View, Window, WindowSize};
+use pathfinder_demo::window::{Event, GamepadAxis, GamepadButton, Keycode, SVGPath, View, Window};
+use pathfinder_demo::window::WindowSize;
 use pathfinder_demo::{DemoApp, Options};
 use pathfinder_geometry::basic::vector::Vector2I;
 use pathfinder_geometry::basic::rect::RectI;
 use pathfinder_gl::GLVersion;
 use pathfinder_gpu::resources::{FilesystemResourceLoader, ResourceLoader};
+use sdl2::controller::{Axis, Button, GameController};
 use sdl2::event::{Event as SDLEvent, WindowEvent};
 use sdl2::keyboard::Keycode as SDLKeycode;
+use sdl2::mouse::MouseWheelDirection;
 use sdl2::video::{GLContext, GLProfile, Window as SDLWindow};
-use sdl2::{EventPump, EventSubsystem, Sdl, VideoSubsystem};
+use sdl2::{EventPump, EventSubsystem, GameControllerSubsystem, Sdl, VideoSubsystem};
 use sdl2_sys::{SDL_Event, SDL_UserEvent};
 use std::path::PathBuf;
 use std::ptr;
@@ -39,20 +42,27 @@ fn main() {
     color_backtrace::install();
     pretty_env_logger::init();
 
-    let window = WindowImpl::new();
+    let mut options = Options::default();
+    options.command_line_overrides();
+
+    let window = WindowImpl::new(options.headless);
     let window_size = window.size();
-    let options = Options::default();
     let mut app = DemoApp::new(window, window_size, options);
 
     while !app.should_exit {
         let mut events = vec![];
-        if !app.dirty {
+        if !app.dirty || app.options.single_step {
             events.push(app.window.get_event());
         }
         while let Some(event) = app.window.try_get_event() {
             events.push(event);
         }
 
+        let events = match app.step_events(events) {
+            Some(events) => events,
+            None => continue,
+        };
+
         let scene_count = app.prepare_frame(events);
         app.draw_scene();
         for scene_index in 0..scene_count {
@@ -66,6 +76,8 @@ thread_local! {
     static SDL_CONTEXT: Sdl = sdl2::init().unwrap();
     static SDL_VIDEO: VideoSubsystem = SDL_CONTEXT.with(|context| context.video().unwrap());
     static SDL_EVENT: EventSubsystem = SDL_CONTEXT.with(|context| context.event().unwrap());
+    static SDL_GAME_CONTROLLER: GameControllerSubsystem =
+        SDL_CONTEXT.with(|context| context.game_controller().unwrap());
 }
 
 struct WindowImpl {
@@ -76,6 +88,8 @@ struct WindowImpl {
     resource_loader: FilesystemResourceLoader,
     selected_file: Option<PathBuf>,
     open_svg_message_type: u32,
+    // SDL only reports events from game controllers that are open.
+    game_controllers: Vec<GameController>,
 }
 
 impl Window for WindowImpl {
@@ -141,7 +155,7 @@ impl Window for WindowImpl {
 }
 
 impl WindowImpl {
-    fn new() -> WindowImpl {
+    fn new(hidden: bool) -> WindowImpl {
         SDL_VIDEO.with(|sdl_video| {
             SDL_EVENT.with(|sdl_event| {
                 let (window, gl_context, event_pump);
@@ -152,17 +166,16 @@ impl WindowImpl {
                 gl_attributes.set_depth_size(24);
                 gl_attributes.set_stencil_size(8);
 
-                window = sdl_video
-                    .window(
-                        "Pathfinder Demo",
-                        DEFAULT_WINDOW_WIDTH,
-                        DEFAULT_WINDOW_HEIGHT,
-                    )
-                    .opengl()
-                    .resizable()
-                    .allow_highdpi()
-                    .build()
-                    .unwrap();
+                let mut window_builder = sdl_video.window(
+                    "Pathfinder Demo",
+                    DEFAULT_WINDOW_WIDTH,
+                    DEFAULT_WINDOW_HEIGHT,
+                );
+                window_builder.opengl().resizable().allow_highdpi();
+                if hidden {
+                    window_builder.hidden();
+                }
+                window = window_builder.build().unwrap();
 
                 gl_context = window.gl_create_context().unwrap();
                 gl::load_with(|name| sdl_video.gl_get_proc_address(name) as *const _);
@@ -173,18 +186,45 @@ impl WindowImpl {
 
                 let open_svg_message_type = unsafe { sdl_event.register_event().unwrap() };
 
-                WindowImpl {
+                // SDL only reports controller events once the game controller subsystem is
+                // initialized, which asking it for the joystick count below does.
+                let mut window_impl = WindowImpl {
                     window,
                     event_pump,
                     gl_context,
                     resource_loader,
                     open_svg_message_type,
                     selected_file: None,
+                    game_controllers: vec![],
+                };
+                let joystick_count = SDL_GAME_CONTROLLER.with(|sdl_game_controller| {
+                    sdl_game_controller.num_joysticks().unwrap_or(0)
+                });
+                for joystick_index in 0..joystick_count {
+                    window_impl.open_game_controller(joystick_index);
                 }
+                window_impl
             })
         })
     }
 
+    // Controllers connected at startup are opened in `new()` and then reported again by SDL, so
+    // skip any that are already open.
+    fn open_game_controller(&mut self, joystick_index: u32) {
+        let game_controller = SDL_GAME_CONTROLLER.with(|sdl_game_controller| {
+            if !sdl_game_controller.is_game_controller(joystick_index) {
+                return None;
+            }
+            sdl_game_controller.open(joystick_index).ok()
+        });
+        if let Some(game_controller) = game_controller {
+            let instance_id = game_controller.instance_id();
+            if self.game_controllers.iter().all(|open| open.instance_id() != instance_id) {
+                self.game_controllers.push(game_controller);
+            }
+        }
+    }
+
     fn size(&self) -> WindowSize {
         let (logical_width, logical_height) = self.window.size();
         let (drawable_width, _) = self.window.drawable_size();
@@ -212,7 +252,7 @@ impl WindowImpl {
         }
     }
 
-    fn convert_sdl_event(&self, sdl_event: SDLEvent) -> Option<Event> {
+    fn convert_sdl_event(&mut self, sdl_event: SDLEvent) -> Option<Event> {
         match sdl_event {
             SDLEvent::User { type_, .. } if type_ == self.open_svg_message_type => Some(
                 Event::OpenSVG(SVGPath::Path(self.selected_file.clone().unwrap())),
@@ -250,6 +290,36 @@ impl WindowImpl {
                 let center = Vector2I::new(mouse_state.x(), mouse_state.y());
                 Some(Event::Zoom(d_dist, center))
             }
+            SDLEvent::MouseWheel { y, direction, .. } => {
+                let delta = match direction {
+                    MouseWheelDirection::Flipped => -y,
+                    _ => y,
+                };
+                let mouse_state = self.event_pump.mouse_state();
+                let position = Vector2I::new(mouse_state.x(), mouse_state.y());
+                Some(Event::Scroll(delta as f32, position))
+            }
+            SDLEvent::ControllerDeviceAdded { which, .. } => {
+                self.open_game_controller(which as u32);
+                None
+            }
+            SDLEvent::ControllerDeviceRemoved { .. } => {
+                self.game_controllers.retain(|game_controller| game_controller.attached());
+                None
+            }
+            SDLEvent::ControllerAxisMotion { axis, value, .. } => {
+                let axis = convert_sdl_axis(axis)?;
+                let value = (value as f32 / i16::max_value() as f32).max(-1.0);
+                Some(Event::GamepadAxis { axis, value })
+            }
+            SDLEvent::ControllerButtonDown { button, .. } => {
+                let button = convert_sdl_button(button)?;
+                Some(Event::GamepadButton { button, pressed: true })
+            }
+            SDLEvent::ControllerButtonUp { button, .. } => {
+                let button = convert_sdl_button(button)?;
+                Some(Event::GamepadButton { button, pressed: false })
+            }
             _ => None,
         }
     }
@@ -257,7 +327,10 @@ impl WindowImpl {
     fn convert_sdl_keycode(&self, sdl_keycode: SDLKeycode) -> Option<Keycode> {
         match sdl_keycode {
             SDLKeycode::Escape => Some(Keycode::Escape),
+            SDLKeycode::Space => Some(Keycode::Space),
             SDLKeycode::Tab => Some(Keycode::Tab),
+            SDLKeycode::PageUp => Some(Keycode::PageUp),
+            SDLKeycode::PageDown => Some(Keycode::PageDown),
             sdl_keycode
                 if sdl_keycode as i32 >= SDLKeycode::A as i32
                     && sdl_keycode as i32 <= SDLKeycode::Z as i32 =>
@@ -269,3 +342,25 @@ impl WindowImpl {
         }
     }
 }
+
+fn convert_sdl_axis(sdl_axis: Axis) -> Option<GamepadAxis> {
+    match sdl_axis {
+        Axis::LeftX => Some(GamepadAxis::LeftX),
+        Axis::LeftY => Some(GamepadAxis::LeftY),
+        Axis::RightX => Some(GamepadAxis::RightX),
+        Axis::RightY => Some(GamepadAxis::RightY),
+        Axis::TriggerLeft | Axis::TriggerRight => None,
+    }
+}
+
+fn convert_sdl_button(sdl_button: Button) -> Option<GamepadButton> {
+    match sdl_button {
+        Button::A => Some(GamepadButton::A),
+        Button::B => Some(GamepadButton::B),
+        Button::X => Some(GamepadButton::X),
+        Button::Y => Some(GamepadButton::Y),
+        Button::Back => Some(GamepadButton::Back),
+        Button::Start => Some(GamepadButton::Start),
+        _ => None,
+    }
+}
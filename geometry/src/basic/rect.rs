@@ -52,15 +52,16 @@ impl RectF {
         Vector2F(self.0.zwxy())
     }
 
+    /// Returns true if the point lies within this rect. Rects are half-open: points on the left
+    /// and top edges are inside, while points on the right and bottom edges are outside.
     #[inline]
     pub fn contains_point(&self, point: Vector2F) -> bool {
-        // self.origin <= point && point <= self.lower_right
-        self.0
-            .concat_xy_xy(point.0)
-            .packed_le(point.0.concat_xy_zw(self.0))
-            .is_all_ones()
+        // self.origin <= point && point < self.lower_right
+        self.min_x() <= point.x() && point.x() < self.max_x() &&
+            self.min_y() <= point.y() && point.y() < self.max_y()
     }
 
+    /// Returns true if `other` lies entirely within this rect. A rect always contains itself.
     #[inline]
     pub fn contains_rect(&self, other: RectF) -> bool {
         // self.origin <= other.origin && other.lower_right <= self.lower_right
@@ -88,6 +89,8 @@ impl RectF {
         )
     }
 
+    /// Returns true if the rects overlap with nonzero area. Rects that merely share an edge or a
+    /// corner do not intersect.
     #[inline]
     pub fn intersects(&self, other: RectF) -> bool {
         // self.origin < other.lower_right && other.origin < self.lower_right
@@ -97,6 +100,8 @@ impl RectF {
             .is_all_ones()
     }
 
+    /// Returns the overlapping area of the two rects, or `None` if they don't intersect (including
+    /// if they merely touch).
     #[inline]
     pub fn intersection(&self, other: RectF) -> Option<RectF> {
         if !self.intersects(other) {
@@ -209,6 +214,8 @@ impl RectI {
         self.0[3]
     }
 
+    /// Returns true if the point lies within this rect. Rects are half-open: points on the left
+    /// and top edges are inside, while points on the right and bottom edges are outside.
     #[inline]
     pub fn contains_point(&self, point: Vector2I) -> bool {
         // self.origin <= point && point <= self.lower_right - 1
@@ -219,8 +226,113 @@ impl RectI {
             .is_all_ones()
     }
 
+    /// Returns true if `other` lies entirely within this rect. A rect always contains itself.
+    #[inline]
+    pub fn contains_rect(&self, other: RectI) -> bool {
+        // self.origin <= other.origin && other.lower_right <= self.lower_right
+        self.min_x() <= other.min_x() && self.min_y() <= other.min_y() &&
+            other.max_x() <= self.max_x() && other.max_y() <= self.max_y()
+    }
+
+    #[inline]
+    pub fn union_rect(&self, other: RectI) -> RectI {
+        RectI::from_points(
+            self.origin().min(other.origin()),
+            self.lower_right().max(other.lower_right()),
+        )
+    }
+
+    /// Returns true if the rects overlap with nonzero area. Rects that merely share an edge or a
+    /// corner do not intersect.
+    #[inline]
+    pub fn intersects(&self, other: RectI) -> bool {
+        self.min_x() < other.max_x() && other.min_x() < self.max_x() &&
+            self.min_y() < other.max_y() && other.min_y() < self.max_y()
+    }
+
+    /// Returns the overlapping area of the two rects, or `None` if they don't intersect (including
+    /// if they merely touch).
+    #[inline]
+    pub fn intersection(&self, other: RectI) -> Option<RectI> {
+        if !self.intersects(other) {
+            None
+        } else {
+            Some(RectI::from_points(
+                self.origin().max(other.origin()),
+                self.lower_right().min(other.lower_right()),
+            ))
+        }
+    }
+
     #[inline]
     pub fn to_f32(&self) -> RectF {
         RectF(self.0.to_f32x4())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::basic::rect::{RectF, RectI};
+    use crate::basic::vector::{Vector2F, Vector2I};
+
+    #[test]
+    fn test_rect_f_contains() {
+        let rect = RectF::new(Vector2F::new(1.0, 2.0), Vector2F::new(3.0, 4.0));
+        assert!(rect.contains_point(Vector2F::new(1.0, 2.0)));
+        assert!(rect.contains_point(Vector2F::new(1.0, 5.0)));
+        assert!(rect.contains_point(Vector2F::new(3.0, 2.0)));
+        assert!(rect.contains_point(Vector2F::new(2.5, 4.0)));
+        assert!(!rect.contains_point(Vector2F::new(4.0, 4.0)));
+        assert!(!rect.contains_point(Vector2F::new(2.5, 6.0)));
+        assert!(!rect.contains_point(Vector2F::new(4.0, 6.0)));
+        assert!(!rect.contains_point(Vector2F::new(0.9, 4.0)));
+
+        assert!(rect.contains_rect(rect));
+        assert!(rect.contains_rect(RectF::new(Vector2F::new(2.0, 3.0), Vector2F::new(2.0, 3.0))));
+        assert!(!rect.contains_rect(RectF::new(Vector2F::new(2.0, 3.0), Vector2F::new(2.5, 3.0))));
+    }
+
+    #[test]
+    fn test_rect_i_contains() {
+        let rect = RectI::new(Vector2I::new(1, 2), Vector2I::new(3, 4));
+        assert!(rect.contains_point(Vector2I::new(1, 2)));
+        assert!(rect.contains_point(Vector2I::new(3, 5)));
+        assert!(!rect.contains_point(Vector2I::new(4, 3)));
+        assert!(!rect.contains_point(Vector2I::new(2, 6)));
+        assert!(!rect.contains_point(Vector2I::new(0, 3)));
+        assert!(!rect.contains_point(Vector2I::new(2, 1)));
+
+        assert!(rect.contains_rect(rect));
+        assert!(rect.contains_rect(RectI::new(Vector2I::new(2, 3), Vector2I::new(2, 3))));
+        assert!(!rect.contains_rect(RectI::new(Vector2I::new(0, 3), Vector2I::new(2, 3))));
+    }
+
+    #[test]
+    fn test_rect_f_intersection_and_union() {
+        let a = RectF::new(Vector2F::new(0.0, 0.0), Vector2F::new(10.0, 10.0));
+        let b = RectF::new(Vector2F::new(5.0, -5.0), Vector2F::new(10.0, 10.0));
+        assert_eq!(a.intersection(b),
+                   Some(RectF::from_points(Vector2F::new(5.0, 0.0), Vector2F::new(10.0, 5.0))));
+        assert_eq!(a.union_rect(b),
+                   RectF::from_points(Vector2F::new(0.0, -5.0), Vector2F::new(15.0, 10.0)));
+
+        let touching = RectF::new(Vector2F::new(10.0, 0.0), Vector2F::new(5.0, 5.0));
+        assert_eq!(a.intersection(touching), None);
+    }
+
+    #[test]
+    fn test_rect_i_intersection_and_union() {
+        let a = RectI::new(Vector2I::new(0, 0), Vector2I::new(10, 10));
+        let b = RectI::new(Vector2I::new(5, -5), Vector2I::new(10, 10));
+        assert_eq!(a.intersection(b),
+                   Some(RectI::from_points(Vector2I::new(5, 0), Vector2I::new(10, 5))));
+        assert_eq!(a.union_rect(b),
+                   RectI::from_points(Vector2I::new(0, -5), Vector2I::new(15, 10)));
+
+        let touching = RectI::new(Vector2I::new(0, 10), Vector2I::new(5, 5));
+        assert!(!a.intersects(touching));
+        assert_eq!(a.intersection(touching), None);
+        let disjoint = RectI::new(Vector2I::new(20, 20), Vector2I::new(5, 5));
+        assert_eq!(a.intersection(disjoint), None);
+    }
+}
@@ -19,6 +19,10 @@ use crate::unit_vector::UnitVector;
 use pathfinder_simd::default::F32x4;
 use std::ops::Sub;
 
+// Transforms whose determinant is smaller than this, relative to the size of their entries, are
+// treated as non-invertible.
+const SINGULAR_DETERMINANT_EPSILON: f32 = 1e-6;
+
 /// A 2x2 matrix, optimized with SIMD, in column-major order.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Matrix2x2F(pub F32x4);
@@ -294,6 +298,42 @@ impl Transform2DF {
     pub fn scale_factor(&self) -> f32 {
         Vector2F(self.matrix.0.zwxy()).length()
     }
+
+    /// Returns the transform that undoes this one, or `None` if this transform collapses the
+    /// plane onto a line or a point (its determinant is zero, relative to its entries).
+    pub fn inverse(&self) -> Option<Transform2DF> {
+        let det = self.matrix.det();
+        let magnitude = (self.m11() * self.m22()).abs() + (self.m12() * self.m21()).abs();
+        if det.is_nan() || det.abs() <= magnitude * SINGULAR_DETERMINANT_EPSILON {
+            return None;
+        }
+        let matrix = self.matrix.inverse();
+        let vector = -matrix.transform_point(self.vector);
+        Some(Transform2DF { matrix, vector })
+    }
+
+    /// Maps a point from the output space of this transform back to its input space.
+    ///
+    /// If you're inverting many points, call `inverse()` once instead.
+    #[inline]
+    pub fn invert_point(&self, point: Vector2F) -> Option<Vector2F> {
+        self.inverse().map(|inverse| inverse.transform_point(point))
+    }
+
+    /// Splits this transform into the translation, rotation angle, and scale that
+    /// `from_scale_rotation_translation()` would build it from.
+    ///
+    /// Skew can't be represented and is dropped. A reflection is folded into a negative X scale,
+    /// so that the rotation stays a proper rotation.
+    pub fn decompose(&self) -> (Vector2F, f32, Vector2F) {
+        let mut scale = Vector2F::new(Vector2F::new(self.m11(), self.m12()).length(),
+                                      Vector2F::new(self.m21(), self.m22()).length());
+        if self.matrix.det() < 0.0 {
+            scale.set_x(-scale.x());
+        }
+        let rotation = f32::atan2(-self.m12() / scale.x(), self.m11() / scale.x());
+        (self.vector, rotation, scale)
+    }
 }
 
 /// Transforms a path with a SIMD 2D transform.
@@ -349,3 +389,61 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::basic::transform2d::Transform2DF;
+    use crate::basic::vector::Vector2F;
+
+    fn approx_eq(a: &Transform2DF, b: &Transform2DF, epsilon: f32) -> bool {
+        let components = |m: &Transform2DF| {
+            [m.m11(), m.m12(), m.m21(), m.m22(), m.translation().x(), m.translation().y()]
+        };
+        components(a).iter().zip(components(b).iter()).all(|(a, b)| (a - b).abs() <= epsilon)
+    }
+
+    #[test]
+    fn test_inverse() {
+        let t = Transform2DF::from_scale_rotation_translation(Vector2F::new(2.0, 0.5),
+                                                              0.7,
+                                                              Vector2F::new(-3.0, 4.0));
+        let inverse = t.inverse().unwrap();
+        assert!(approx_eq(&inverse.pre_mul(&t), &Transform2DF::default(), 0.0001));
+        assert!(approx_eq(&inverse.post_mul(&t), &Transform2DF::default(), 0.0001));
+
+        let point = Vector2F::new(5.0, -6.0);
+        let inverted_point = t.invert_point(t.transform_point(point)).unwrap();
+        assert!((inverted_point - point).length() < 0.0001);
+
+        let singular = Transform2DF::from_scale(Vector2F::new(2.0, 0.0));
+        assert!(singular.inverse().is_none());
+        assert!(singular.invert_point(point).is_none());
+    }
+
+    #[test]
+    fn test_decompose() {
+        for &(scale, theta) in &[(Vector2F::new(2.0, 3.0), 0.5),
+                                 (Vector2F::new(0.5, 0.5), -2.0),
+                                 (Vector2F::new(-2.0, 3.0), 1.0)] {
+            let translation = Vector2F::new(10.0, -20.0);
+            let m = Transform2DF::from_scale_rotation_translation(scale, theta, translation);
+
+            let (decomposed_translation, decomposed_theta, decomposed_scale) = m.decompose();
+            assert!((decomposed_translation - translation).length() < 0.0001);
+            assert!((decomposed_theta - theta).abs() < 0.0001);
+            assert!((decomposed_scale - scale).length() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_decompose_reflection() {
+        // A reflection in Y comes back as a reflection in X plus a half turn.
+        let m = Transform2DF::from_scale_rotation_translation(Vector2F::new(2.0, -3.0),
+                                                              0.5,
+                                                              Vector2F::new(1.0, 2.0));
+        let (translation, theta, scale) = m.decompose();
+        assert!(scale.x() < 0.0 && scale.y() > 0.0);
+        let recomposed = Transform2DF::from_scale_rotation_translation(scale, theta, translation);
+        assert!(approx_eq(&recomposed, &m, 0.0001));
+    }
+}
@@ -17,6 +17,8 @@ use crate::segment::Segment;
 use pathfinder_simd::default::F32x4;
 use std::ops::{Add, Neg};
 
+const QUATERNION_SLERP_EPSILON: f32 = 0.0005;
+
 /// An transform, optimized with SIMD.
 ///
 /// In column-major order.
@@ -296,6 +298,60 @@ impl Transform3DF {
         Transform3DF::from_submatrices(a_new, b_new, c_new, d_new)
     }
 
+    /// Splits this transform into a translation, a rotation, and a scale, such that translating
+    /// by the result of rotating by the result of scaling reproduces it.
+    ///
+    /// This assumes the transform is affine (its bottom row is 0, 0, 0, 1) and that its upper
+    /// left 3x3 submatrix is invertible. Skew, which arises from scaling non-uniformly *after*
+    /// rotating, can't be represented and is dropped. A reflection is folded into a negative X
+    /// scale, so that the rotation stays a proper rotation.
+    pub fn decompose(&self) -> DecomposedTransform3DF {
+        let translation = Vector4F::new(self.c3[0], self.c3[1], self.c3[2], 1.0);
+
+        let column_length = |column: F32x4| {
+            (column[0] * column[0] + column[1] * column[1] + column[2] * column[2]).sqrt()
+        };
+        let mut scale = Vector4F::new(column_length(self.c0),
+                                      column_length(self.c1),
+                                      column_length(self.c2),
+                                      1.0);
+
+        // A negative determinant means the transform flips handedness.
+        let (c0, c1, c2) = (self.c0, self.c1, self.c2);
+        let det = c0[0] * (c1[1] * c2[2] - c2[1] * c1[2]) -
+            c1[0] * (c0[1] * c2[2] - c2[1] * c0[2]) +
+            c2[0] * (c0[1] * c1[2] - c1[1] * c0[2]);
+        if det < 0.0 {
+            scale.set_x(-scale.x());
+        }
+
+        // Normalize the columns to get a pure rotation matrix.
+        let (r0, r1, r2) = (c0 * F32x4::splat(1.0 / scale.x()),
+                            c1 * F32x4::splat(1.0 / scale.y()),
+                            c2 * F32x4::splat(1.0 / scale.z()));
+        let (m00, m01, m02) = (r0[0], r1[0], r2[0]);
+        let (m10, m11, m12) = (r0[1], r1[1], r2[1]);
+        let (m20, m21, m22) = (r0[2], r1[2], r2[2]);
+
+        // Convert to a quaternion, branching on the largest diagonal term for stability.
+        let trace = m00 + m11 + m22;
+        let rotation = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            F32x4::new((m21 - m12) / s, (m02 - m20) / s, (m10 - m01) / s, 0.25 * s)
+        } else if m00 > m11 && m00 > m22 {
+            let s = (1.0 + m00 - m11 - m22).sqrt() * 2.0;
+            F32x4::new(0.25 * s, (m01 + m10) / s, (m02 + m20) / s, (m21 - m12) / s)
+        } else if m11 > m22 {
+            let s = (1.0 + m11 - m00 - m22).sqrt() * 2.0;
+            F32x4::new((m01 + m10) / s, 0.25 * s, (m12 + m21) / s, (m02 - m20) / s)
+        } else {
+            let s = (1.0 + m22 - m00 - m11).sqrt() * 2.0;
+            F32x4::new((m02 + m20) / s, (m12 + m21) / s, 0.25 * s, (m10 - m01) / s)
+        };
+
+        DecomposedTransform3DF { translation, rotation, scale }
+    }
+
     pub fn approx_eq(&self, other: &Transform3DF, epsilon: f32) -> bool {
         self.c0.approx_eq(other.c0, epsilon)
             && self.c1.approx_eq(other.c1, epsilon)
@@ -309,6 +365,27 @@ impl Transform3DF {
     }
 }
 
+/// A 3D transform split into its parts by `Transform3DF::decompose()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DecomposedTransform3DF {
+    /// The translation. The `w` component is always 1.
+    pub translation: Vector4F,
+    /// A unit rotation quaternion, packed as in `Transform3DF::from_rotation_quaternion()`.
+    pub rotation: F32x4,
+    /// The scale along each axis, applied before rotating. The `w` component is always 1.
+    pub scale: Vector4F,
+}
+
+impl DecomposedTransform3DF {
+    /// Recomposes the parts into a single transform.
+    pub fn to_transform(&self) -> Transform3DF {
+        let (translation, scale) = (self.translation, self.scale);
+        Transform3DF::from_translation(translation.x(), translation.y(), translation.z())
+            .post_mul(&Transform3DF::from_rotation_quaternion(self.rotation))
+            .post_mul(&Transform3DF::from_scale(scale.x(), scale.y(), scale.z()))
+    }
+}
+
 impl Add<Matrix2x2F> for Matrix2x2F {
     type Output = Matrix2x2F;
     #[inline]
@@ -351,6 +428,22 @@ impl Perspective {
         (point + Vector2F::splat(1.0)) * self.window_size.to_f32().scale(0.5)
     }
 
+    /// Maps a point in window coordinates back through this projection, inverting
+    /// `transform_point_2d()`. `depth` is the depth in normalized device coordinates, from -1 at
+    /// the near plane to 1 at the far plane.
+    ///
+    /// Unprojecting the same window point at two different depths yields a ray through the scene,
+    /// which is useful for picking.
+    pub fn unproject(&self, window_point: Vector2F, depth: f32) -> Vector4F {
+        let window_size = self.window_size.to_f32();
+        let scale = Vector2F::new(2.0 / window_size.x(), 2.0 / window_size.y());
+        let ndc = (window_point.scale_xy(scale) - Vector2F::splat(1.0)) * Vector2F::new(1.0, -1.0);
+        self.transform
+            .inverse()
+            .transform_point(Vector4F::new(ndc.x(), ndc.y(), depth, 1.0))
+            .perspective_divide()
+    }
+
     // TODO(pcwalton): SIMD?
     #[inline]
     pub fn transform_rect(&self, rect: RectF) -> RectF {
@@ -372,6 +465,36 @@ impl Perspective {
     }
 }
 
+/// Spherically interpolates between two unit rotation quaternions, packed as in
+/// `Transform3DF::from_rotation_quaternion()`.
+///
+/// Unlike interpolating Euler angles, this rotates at a constant angular speed along the shortest
+/// arc between the two orientations.
+pub fn slerp_rotation_quaternions(q0: F32x4, mut q1: F32x4, t: f32) -> F32x4 {
+    let product = q0 * q1;
+    let mut cos_theta = product[0] + product[1] + product[2] + product[3];
+
+    // `q` and `-q` are the same rotation; pick the one that takes the short way around.
+    if cos_theta < 0.0 {
+        q1 = F32x4::splat(0.0) - q1;
+        cos_theta = -cos_theta;
+    }
+
+    // Very close orientations would divide by a tiny sine, so fall back to a normalized lerp.
+    if cos_theta > 1.0 - QUATERNION_SLERP_EPSILON {
+        let q = q0 + (q1 - q0) * F32x4::splat(t);
+        let length_sq = q * q;
+        let length = (length_sq[0] + length_sq[1] + length_sq[2] + length_sq[3]).sqrt();
+        return q * F32x4::splat(1.0 / length);
+    }
+
+    let theta = cos_theta.acos();
+    let sin_theta = theta.sin();
+    let weight0 = (theta * (1.0 - t)).sin() / sin_theta;
+    let weight1 = (theta * t).sin() / sin_theta;
+    q0 * F32x4::splat(weight0) + q1 * F32x4::splat(weight1)
+}
+
 /// Transforms a path with a perspective projection.
 pub struct PerspectivePathIter<I>
 where
@@ -429,8 +552,10 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::basic::vector::Vector4F;
-    use crate::basic::transform3d::Transform3DF;
+    use crate::basic::vector::{Vector2F, Vector2I, Vector4F};
+    use crate::basic::transform3d::{self, Perspective, Transform3DF};
+    use pathfinder_simd::default::F32x4;
+    use std::f32::consts::{FRAC_PI_4, FRAC_PI_8};
 
     #[test]
     fn test_post_mul() {
@@ -505,4 +630,66 @@ mod test {
         let p2 = m_inv.transform_point(p1);
         assert!(p0.approx_eq(&p2, 0.0001));
     }
+
+    #[test]
+    fn test_unproject() {
+        let transform = Transform3DF::from_perspective(FRAC_PI_4, 4.0 / 3.0, 0.1, 100.0)
+            .post_mul(&Transform3DF::from_rotation(FRAC_PI_8, -FRAC_PI_8, 0.0))
+            .post_mul(&Transform3DF::from_translation(0.5, -0.25, -3.0));
+        let perspective = Perspective::new(&transform, Vector2I::new(800, 600));
+
+        for &point in &[Vector2F::new(0.0, 0.0), Vector2F::new(0.3, -0.7)] {
+            let window_point = perspective.transform_point_2d(&point);
+            let depth = transform.transform_point(point.to_3d()).perspective_divide().z();
+            let unprojected = perspective.unproject(window_point, depth);
+            assert!(unprojected.approx_eq(&point.to_3d(), 0.0001));
+        }
+    }
+
+    #[test]
+    fn test_slerp_rotation_quaternions() {
+        // Identity, and a quarter turn around the Z axis.
+        let q0 = F32x4::new(0.0, 0.0, 0.0, 1.0);
+        let q1 = F32x4::new(0.0, 0.0, FRAC_PI_4.sin(), FRAC_PI_4.cos());
+
+        let start = transform3d::slerp_rotation_quaternions(q0, q1, 0.0);
+        let end = transform3d::slerp_rotation_quaternions(q0, q1, 1.0);
+        assert!(Vector4F(start).approx_eq(&Vector4F(q0), 0.0001));
+        assert!(Vector4F(end).approx_eq(&Vector4F(q1), 0.0001));
+
+        // Halfway is an eighth of a turn.
+        let mid = transform3d::slerp_rotation_quaternions(q0, q1, 0.5);
+        let expected_mid = F32x4::new(0.0, 0.0, FRAC_PI_8.sin(), FRAC_PI_8.cos());
+        assert!(Vector4F(mid).approx_eq(&Vector4F(expected_mid), 0.0001));
+
+        // The negated quaternion is the same rotation, so the result must not take the long way.
+        let negated_q1 = F32x4::splat(0.0) - q1;
+        let mid = transform3d::slerp_rotation_quaternions(q0, negated_q1, 0.5);
+        assert!(Vector4F(mid).approx_eq(&Vector4F(expected_mid), 0.0001));
+    }
+
+    #[test]
+    fn test_decompose() {
+        let translation = Transform3DF::from_translation(1.0, -2.0, 3.0);
+        let rotation = Transform3DF::from_rotation(0.3, 0.5, -0.2);
+        let scale = Transform3DF::from_scale(2.0, 3.0, 0.5);
+        let m = translation.post_mul(&rotation).post_mul(&scale);
+
+        let decomposed = m.decompose();
+        assert!(decomposed.translation.approx_eq(&Vector4F::new(1.0, -2.0, 3.0, 1.0), 0.0001));
+        assert!(decomposed.scale.approx_eq(&Vector4F::new(2.0, 3.0, 0.5, 1.0), 0.0001));
+        assert!(Transform3DF::from_rotation_quaternion(decomposed.rotation)
+            .approx_eq(&rotation, 0.0001));
+        assert!(decomposed.to_transform().approx_eq(&m, 0.0001));
+    }
+
+    #[test]
+    fn test_decompose_reflection() {
+        let rotation = Transform3DF::from_rotation(2.5, -1.0, 3.0);
+        let m = rotation.post_mul(&Transform3DF::from_scale(1.0, -1.0, 1.0));
+
+        let decomposed = m.decompose();
+        assert!(decomposed.scale.x() < 0.0);
+        assert!(decomposed.to_transform().approx_eq(&m, 0.0001));
+    }
 }
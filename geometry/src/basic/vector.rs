@@ -113,9 +113,21 @@ impl Vector2F {
     }
 
     /// Treats this point as a vector and normalizes it.
+    ///
+    /// The zero vector has no direction, so it normalizes to itself rather than to NaN.
     #[inline]
     pub fn normalize(&self) -> Vector2F {
-        self.scale(1.0 / self.length())
+        let length = self.length();
+        if length == 0.0 {
+            return *self;
+        }
+        self.scale(1.0 / length)
+    }
+
+    /// Returns the distance between this point and `other`.
+    #[inline]
+    pub fn distance_to(&self, other: Vector2F) -> f32 {
+        (other - *self).length()
     }
 
     /// Swaps y and x.
@@ -215,6 +227,16 @@ impl Vector2I {
         self.0[1] = y;
     }
 
+    #[inline]
+    pub fn min(&self, other: Vector2I) -> Vector2I {
+        Vector2I(self.0.min(other.0))
+    }
+
+    #[inline]
+    pub fn max(&self, other: Vector2I) -> Vector2I {
+        Vector2I(self.0.max(other.0))
+    }
+
     #[inline]
     pub fn scale(&self, factor: i32) -> Vector2I {
         Vector2I(self.0 * I32x4::splat(factor))
@@ -385,3 +407,36 @@ impl Default for Vector4F {
         Vector4F(point)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::basic::vector::{Vector2F, Vector4F};
+
+    #[test]
+    fn test_normalize() {
+        let normalized = Vector2F::new(3.0, -4.0).normalize();
+        assert!((normalized.x() - 0.6).abs() < 0.0001 && (normalized.y() + 0.8).abs() < 0.0001);
+        assert_eq!(Vector2F::default().normalize(), Vector2F::default());
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let (a, b) = (Vector2F::new(1.0, 2.0), Vector2F::new(4.0, 6.0));
+        assert_eq!(a.distance_to(b), 5.0);
+        assert_eq!(b.distance_to(a), 5.0);
+        assert_eq!(a.distance_to(a), 0.0);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let (a, b) = (Vector2F::new(1.0, -2.0), Vector2F::new(5.0, 4.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Vector2F::new(3.0, 1.0));
+
+        let (a, b) = (Vector4F::new(1.0, -2.0, 3.0, 1.0), Vector4F::new(5.0, 4.0, -3.0, 1.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Vector4F::new(3.0, 1.0, 0.0, 1.0));
+    }
+}
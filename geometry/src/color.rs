@@ -8,6 +8,7 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use crate::util;
 use pathfinder_simd::default::F32x4;
 use std::fmt::{self, Debug, Formatter};
 
@@ -56,6 +57,115 @@ impl ColorU {
     pub fn is_fully_transparent(&self) -> bool {
         self.a == 0
     }
+
+    /// Composites this color over `background` with the source-over operator. Both colors use
+    /// straight (not premultiplied) alpha.
+    pub fn blend_over(&self, background: ColorU) -> ColorU {
+        let (source, background) = (self.to_f32(), background.to_f32());
+        let alpha = source.a() + background.a() * (1.0 - source.a());
+        if alpha == 0.0 {
+            return ColorU::transparent_black();
+        }
+
+        let source_weight = F32x4::splat(source.a() / alpha);
+        let background_weight = F32x4::splat(background.a() * (1.0 - source.a()) / alpha);
+        let mut color = source.0 * source_weight + background.0 * background_weight;
+        color[3] = alpha;
+        ColorF(color).to_u8()
+    }
+
+    /// Linearly interpolates between this color and `other`. `t` is clamped to the range 0 to 1.
+    #[inline]
+    pub fn lerp(&self, other: ColorU, t: f32) -> ColorU {
+        self.to_f32().lerp(other.to_f32(), util::clamp(t, 0.0, 1.0)).to_u8()
+    }
+
+    /// Creates a color from a hue in degrees and a saturation and value in the range 0 to 1.
+    ///
+    /// Hues outside the range 0 to 360 wrap around, and out-of-range saturations and values are
+    /// clamped.
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32, alpha: u8) -> ColorU {
+        let saturation = util::clamp(saturation, 0.0, 1.0);
+        let value = util::clamp(value, 0.0, 1.0);
+        let chroma = value * saturation;
+        ColorU::from_hue_chroma(hue, chroma, value - chroma, alpha)
+    }
+
+    /// Creates a color from a hue in degrees and a saturation and lightness in the range 0 to 1.
+    ///
+    /// Hues outside the range 0 to 360 wrap around, and out-of-range saturations and lightnesses
+    /// are clamped.
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32, alpha: u8) -> ColorU {
+        let saturation = util::clamp(saturation, 0.0, 1.0);
+        let lightness = util::clamp(lightness, 0.0, 1.0);
+        let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+        ColorU::from_hue_chroma(hue, chroma, lightness - chroma * 0.5, alpha)
+    }
+
+    /// Returns the hue in degrees and the saturation and value in the range 0 to 1. Grays have a
+    /// hue of 0.
+    pub fn to_hsv(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let saturation = if max == 0.0 { 0.0 } else { (max - min) / max };
+        (hue, saturation, max)
+    }
+
+    /// Returns the hue in degrees and the saturation and lightness in the range 0 to 1. Grays
+    /// have a hue of 0.
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (hue, max, min) = self.hue_max_min();
+        let lightness = (max + min) * 0.5;
+        let saturation = if max == min {
+            0.0
+        } else {
+            (max - min) / (1.0 - (2.0 * lightness - 1.0).abs())
+        };
+        (hue, saturation, lightness)
+    }
+
+    fn from_hue_chroma(hue: f32, chroma: f32, offset: f32, alpha: u8) -> ColorU {
+        let mut hue = hue % 360.0;
+        if hue < 0.0 {
+            hue += 360.0;
+        }
+
+        let sector = hue / 60.0;
+        let x = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+        let (r, g, b) = match sector as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        ColorF(F32x4::new(r + offset, g + offset, b + offset, alpha as f32 / 255.0)).to_u8()
+    }
+
+    // Returns the hue in degrees, along with the largest and smallest of the RGB components.
+    fn hue_max_min(&self) -> (f32, f32, f32) {
+        let color = self.to_f32();
+        let (r, g, b) = (color.r(), color.g(), color.b());
+        let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+        let chroma = max - min;
+
+        let sector = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            (g - b) / chroma
+        } else if max == g {
+            (b - r) / chroma + 2.0
+        } else {
+            (r - g) / chroma + 4.0
+        };
+
+        let mut hue = sector * 60.0;
+        if hue < 0.0 {
+            hue += 360.0;
+        }
+        (hue, max, min)
+    }
 }
 
 impl Debug for ColorU {
@@ -120,3 +230,77 @@ impl ColorF {
         self.0[3]
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::color::ColorU;
+
+    const EPSILON: f32 = 0.001;
+
+    fn approx_eq(a: (f32, f32, f32), b: (f32, f32, f32)) -> bool {
+        (a.0 - b.0).abs() < EPSILON && (a.1 - b.1).abs() < EPSILON && (a.2 - b.2).abs() < EPSILON
+    }
+
+    fn rgb(r: u8, g: u8, b: u8) -> ColorU {
+        ColorU { r, g, b, a: 255 }
+    }
+
+    #[test]
+    fn test_blend_over() {
+        let background = ColorU { r: 10, g: 200, b: 30, a: 255 };
+        let opaque = rgb(200, 100, 51);
+        assert_eq!(opaque.blend_over(background), opaque);
+        assert_eq!(opaque.blend_over(ColorU::transparent_black()), opaque);
+
+        let transparent = ColorU { r: 255, g: 255, b: 255, a: 0 };
+        assert_eq!(transparent.blend_over(background), background);
+        assert_eq!(transparent.blend_over(ColorU::transparent_black()),
+                   ColorU::transparent_black());
+
+        let translucent = ColorU { r: 255, g: 0, b: 0, a: 128 };
+        assert_eq!(translucent.blend_over(rgb(0, 0, 255)), rgb(128, 0, 127));
+        assert_eq!(translucent.blend_over(ColorU::transparent_black()), translucent);
+    }
+
+    #[test]
+    fn test_lerp() {
+        let (a, b) = (rgb(0, 100, 255), ColorU { r: 255, g: 200, b: 0, a: 0 });
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), ColorU { r: 128, g: 150, b: 128, a: 128 });
+        assert_eq!(a.lerp(b, 2.0), b);
+    }
+
+    #[test]
+    fn test_hsv() {
+        assert!(approx_eq(rgb(255, 0, 0).to_hsv(), (0.0, 1.0, 1.0)));
+        assert!(approx_eq(rgb(0, 255, 0).to_hsv(), (120.0, 1.0, 1.0)));
+        assert!(approx_eq(rgb(0, 0, 255).to_hsv(), (240.0, 1.0, 1.0)));
+        assert!(approx_eq(rgb(128, 128, 128).to_hsv(), (0.0, 0.0, 128.0 / 255.0)));
+
+        assert_eq!(ColorU::from_hsv(0.0, 1.0, 1.0, 255), rgb(255, 0, 0));
+        assert_eq!(ColorU::from_hsv(120.0, 1.0, 1.0, 255), rgb(0, 255, 0));
+        assert_eq!(ColorU::from_hsv(240.0, 1.0, 1.0, 255), rgb(0, 0, 255));
+        assert_eq!(ColorU::from_hsv(0.0, 0.0, 128.0 / 255.0, 255), rgb(128, 128, 128));
+        assert_eq!(ColorU::from_hsv(60.0, 1.0, 1.0, 64), ColorU { r: 255, g: 255, b: 0, a: 64 });
+
+        // Out-of-range inputs wrap or clamp.
+        assert_eq!(ColorU::from_hsv(-120.0, 2.0, 1.5, 255), rgb(0, 0, 255));
+        assert_eq!(ColorU::from_hsv(480.0, 1.0, 1.0, 255), rgb(0, 255, 0));
+    }
+
+    #[test]
+    fn test_hsl() {
+        assert!(approx_eq(rgb(255, 0, 0).to_hsl(), (0.0, 1.0, 0.5)));
+        assert!(approx_eq(rgb(0, 255, 0).to_hsl(), (120.0, 1.0, 0.5)));
+        assert!(approx_eq(rgb(0, 0, 255).to_hsl(), (240.0, 1.0, 0.5)));
+        assert!(approx_eq(rgb(128, 128, 128).to_hsl(), (0.0, 0.0, 128.0 / 255.0)));
+        assert!(approx_eq(rgb(255, 255, 255).to_hsl(), (0.0, 0.0, 1.0)));
+
+        assert_eq!(ColorU::from_hsl(0.0, 1.0, 0.5, 255), rgb(255, 0, 0));
+        assert_eq!(ColorU::from_hsl(120.0, 1.0, 0.5, 255), rgb(0, 255, 0));
+        assert_eq!(ColorU::from_hsl(240.0, 1.0, 0.5, 255), rgb(0, 0, 255));
+        assert_eq!(ColorU::from_hsl(0.0, 0.0, 128.0 / 255.0, 255), rgb(128, 128, 128));
+        assert_eq!(ColorU::from_hsl(0.0, -1.0, 2.0, 255), rgb(255, 255, 255));
+    }
+}
@@ -107,6 +107,9 @@ impl<'a> DashState<'a> {
     fn new(dashes: &'a [f32], mut offset: f32) -> DashState<'a> {
         let total: f32 = dashes.iter().cloned().sum();
         offset %= total;
+        if offset < 0.0 {
+            offset += total;
+        }
 
         let mut current_dash_index = 0;
         while current_dash_index < dashes.len() {
@@ -117,12 +120,16 @@ impl<'a> DashState<'a> {
             offset -= dash;
             current_dash_index += 1;
         }
+        if current_dash_index == dashes.len() {
+            current_dash_index = 0;
+            offset = 0.0;
+        }
 
         DashState {
             output: Contour::new(),
             dashes,
             current_dash_index,
-            distance_left: offset,
+            distance_left: dashes[current_dash_index] - offset,
         }
     }
 
@@ -131,3 +138,46 @@ impl<'a> DashState<'a> {
         self.current_dash_index % 2 == 0
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::basic::vector::Vector2F;
+    use crate::outline::{Contour, Outline};
+    use super::OutlineDash;
+
+    fn dash_line(dashes: &[f32], offset: f32) -> Vec<(Vector2F, Vector2F)> {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_endpoint(Vector2F::new(10.0, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+
+        let mut dash = OutlineDash::new(&outline, dashes, offset);
+        dash.dash();
+        dash.into_outline()
+            .contours()
+            .iter()
+            .map(|contour| (contour.position_of(0), contour.last_position().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn test_dash_starts_on() {
+        assert_eq!(dash_line(&[2.0, 2.0], 0.0), vec![
+            (Vector2F::new(0.0, 0.0), Vector2F::new(2.0, 0.0)),
+            (Vector2F::new(4.0, 0.0), Vector2F::new(6.0, 0.0)),
+            (Vector2F::new(8.0, 0.0), Vector2F::new(10.0, 0.0)),
+        ]);
+    }
+
+    #[test]
+    fn test_dash_offset() {
+        let expected = vec![
+            (Vector2F::new(0.0, 0.0), Vector2F::new(1.0, 0.0)),
+            (Vector2F::new(3.0, 0.0), Vector2F::new(5.0, 0.0)),
+            (Vector2F::new(7.0, 0.0), Vector2F::new(9.0, 0.0)),
+        ];
+        assert_eq!(dash_line(&[2.0, 2.0], 1.0), expected);
+        assert_eq!(dash_line(&[2.0, 2.0], -3.0), expected);
+    }
+}
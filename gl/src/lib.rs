@@ -17,13 +17,14 @@ use gl::types::{GLboolean, GLchar, GLenum, GLfloat, GLint, GLsizei, GLsizeiptr,
 use pathfinder_geometry::basic::vector::Vector2I;
 use pathfinder_geometry::basic::rect::RectI;
 use pathfinder_gpu::resources::ResourceLoader;
-use pathfinder_gpu::{BlendState, BufferData, BufferTarget, BufferUploadMode, ClearParams};
-use pathfinder_gpu::{DepthFunc, Device, Primitive, RenderState, ShaderKind, StencilFunc};
-use pathfinder_gpu::{TextureFormat, UniformData, VertexAttrClass};
+use pathfinder_gpu::{AdapterInfo, BlendState, BufferData, BufferTarget, BufferUploadMode};
+use pathfinder_gpu::{ClearParams, DepthFunc, Device, Primitive, RenderState, ShaderKind};
+use pathfinder_gpu::{StencilFunc, TextureFormat, UniformData, VertexAttrClass};
 use pathfinder_gpu::{VertexAttrDescriptor, VertexAttrType};
 use pathfinder_simd::default::F32x4;
-use std::ffi::CString;
+use std::ffi::{CStr, CString};
 use std::mem;
+use std::os::raw::c_char;
 use std::ptr;
 use std::str;
 use std::time::Duration;
@@ -467,28 +468,12 @@ impl Device for GLDevice {
     }
 
     fn read_pixels_from_default_framebuffer(&self, size: Vector2I) -> Vec<u8> {
-        let mut pixels = vec![0; size.x() as usize * size.y() as usize * 4];
-        unsafe {
-            gl::BindFramebuffer(gl::FRAMEBUFFER, self.default_framebuffer); ck();
-            gl::ReadPixels(0,
-                           0,
-                           size.x() as GLsizei,
-                           size.y() as GLsizei,
-                           gl::RGBA,
-                           gl::UNSIGNED_BYTE,
-                           pixels.as_mut_ptr() as *mut GLvoid); ck();
-        }
-
-        // Flip right-side-up.
-        let stride = size.x() as usize * 4;
-        for y in 0..(size.y() as usize / 2) {
-            let (index_a, index_b) = (y * stride, (size.y() as usize - y - 1) * stride);
-            for offset in 0..stride {
-                pixels.swap(index_a + offset, index_b + offset);
-            }
-        }
+        self.read_pixels(self.default_framebuffer, size)
+    }
 
-        pixels
+    fn read_pixels_from_framebuffer(&self, framebuffer: &GLFramebuffer, size: Vector2I)
+                                    -> Vec<u8> {
+        self.read_pixels(framebuffer.gl_framebuffer, size)
     }
 
     fn clear(&self, params: &ClearParams) {
@@ -601,6 +586,14 @@ impl Device for GLDevice {
         }
     }
 
+    fn adapter_info(&self) -> AdapterInfo {
+        AdapterInfo {
+            vendor: get_gl_string(gl::VENDOR),
+            renderer: get_gl_string(gl::RENDERER),
+            version: get_gl_string(gl::VERSION),
+        }
+    }
+
     #[inline]
     fn bind_vertex_array(&self, vertex_array: &GLVertexArray) {
         unsafe {
@@ -644,6 +637,31 @@ impl Device for GLDevice {
 }
 
 impl GLDevice {
+    fn read_pixels(&self, gl_framebuffer: GLuint, size: Vector2I) -> Vec<u8> {
+        let mut pixels = vec![0; size.x() as usize * size.y() as usize * 4];
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, gl_framebuffer); ck();
+            gl::ReadPixels(0,
+                           0,
+                           size.x() as GLsizei,
+                           size.y() as GLsizei,
+                           gl::RGBA,
+                           gl::UNSIGNED_BYTE,
+                           pixels.as_mut_ptr() as *mut GLvoid); ck();
+        }
+
+        // Flip right-side-up.
+        let stride = size.x() as usize * 4;
+        for y in 0..(size.y() as usize / 2) {
+            let (index_a, index_b) = (y * stride, (size.y() as usize - y - 1) * stride);
+            for offset in 0..stride {
+                pixels.swap(index_a + offset, index_b + offset);
+            }
+        }
+
+        pixels
+    }
+
     fn preprocess(&self, output: &mut Vec<u8>, source: &[u8], version: &str) {
         let mut index = 0;
         while index < source.len() {
@@ -832,6 +850,9 @@ impl DepthFuncExt for DepthFunc {
     fn to_gl_depth_func(self) -> GLenum {
         match self {
             DepthFunc::Less => gl::LESS,
+            DepthFunc::LessEqual => gl::LEQUAL,
+            DepthFunc::Greater => gl::GREATER,
+            DepthFunc::GreaterEqual => gl::GEQUAL,
             DepthFunc::Always => gl::ALWAYS,
         }
     }
@@ -899,6 +920,16 @@ impl GLVersion {
     }
 }
 
+fn get_gl_string(name: GLenum) -> String {
+    unsafe {
+        let string = gl::GetString(name); ck();
+        if string.is_null() {
+            return String::new();
+        }
+        CStr::from_ptr(string as *const c_char).to_string_lossy().into_owned()
+    }
+}
+
 // Error checking
 
 #[cfg(debug_assertions)]
@@ -62,6 +62,8 @@ pub trait Device {
     fn texture_size(&self, texture: &Self::Texture) -> Vector2I;
     fn upload_to_texture(&self, texture: &Self::Texture, size: Vector2I, data: &[u8]);
     fn read_pixels_from_default_framebuffer(&self, size: Vector2I) -> Vec<u8>;
+    fn read_pixels_from_framebuffer(&self, framebuffer: &Self::Framebuffer, size: Vector2I)
+                                    -> Vec<u8>;
     fn clear(&self, params: &ClearParams);
     fn draw_arrays(&self, primitive: Primitive, index_count: u32, render_state: &RenderState);
     fn draw_elements(&self, primitive: Primitive, index_count: u32, render_state: &RenderState);
@@ -75,6 +77,7 @@ pub trait Device {
     fn end_timer_query(&self, query: &Self::TimerQuery);
     fn timer_query_is_available(&self, query: &Self::TimerQuery) -> bool;
     fn get_timer_query(&self, query: &Self::TimerQuery) -> Duration;
+    fn adapter_info(&self) -> AdapterInfo;
 
     // TODO(pcwalton): Go bindless...
     fn bind_vertex_array(&self, vertex_array: &Self::VertexArray);
@@ -213,6 +216,9 @@ pub struct DepthState {
 #[derive(Clone, Copy, Debug)]
 pub enum DepthFunc {
     Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
     Always,
 }
 
@@ -283,6 +289,14 @@ impl UniformData {
     }
 }
 
+/// Identifies the graphics driver and API version in use, for diagnostics.
+#[derive(Clone, Debug)]
+pub struct AdapterInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub version: String,
+}
+
 #[derive(Clone, Copy, Debug)]
 pub struct VertexAttrDescriptor {
     pub size: usize,
@@ -14,7 +14,7 @@ use crate::concurrent::executor::Executor;
 use crate::gpu_data::{AlphaTileBatchPrimitive, BuiltObject, FillBatchPrimitive, RenderCommand};
 use crate::options::{PreparedRenderOptions, RenderCommandListener};
 use crate::scene::Scene;
-use crate::tile_map::DenseTileMap;
+use crate::tile_map::{DenseTileMap, TileMap};
 use crate::tiles::{self, TILE_HEIGHT, TILE_WIDTH, Tiler};
 use crate::z_buffer::ZBuffer;
 use pathfinder_geometry::basic::line_segment::{LineSegment2F, LineSegmentU4, LineSegmentU8};
@@ -80,6 +80,10 @@ impl<'a> SceneBuilder<'a> {
         scene: &Scene,
     ) -> Vec<AlphaTileBatchPrimitive> {
         let path_object = &scene.paths[path_index];
+        if built_options.culls_path_with_bounds(path_object.outline().bounds()) {
+            return vec![];
+        }
+
         let outline = scene.apply_render_options(path_object.outline(), built_options);
         let paint_id = path_object.paint();
         let object_is_opaque = scene.paints[paint_id.0 as usize].is_opaque();
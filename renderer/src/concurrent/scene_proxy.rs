@@ -26,6 +26,9 @@ use crate::options::{RenderCommandListener, RenderOptions};
 use crate::scene::Scene;
 use pathfinder_geometry::basic::rect::RectF;
 use pathfinder_gpu::Device;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::thread;
 
@@ -33,6 +36,9 @@ const MAX_MESSAGES_IN_FLIGHT: usize = 1024;
 
 pub struct SceneProxy {
     sender: Sender<MainToWorkerMsg>,
+    // The epoch of the most recently requested build. Builds from earlier epochs are abandoned
+    // without sending `RenderCommand::Finish`.
+    latest_build_epoch: Arc<AtomicU64>,
 }
 
 impl SceneProxy {
@@ -43,8 +49,12 @@ impl SceneProxy {
     pub fn from_scene<E>(scene: Scene, executor: E) -> SceneProxy
                          where E: Executor + Send + 'static {
         let (main_to_worker_sender, main_to_worker_receiver) = mpsc::channel();
-        thread::spawn(move || scene_thread(scene, executor, main_to_worker_receiver));
-        SceneProxy { sender: main_to_worker_sender }
+        let latest_build_epoch = Arc::new(AtomicU64::new(0));
+        let worker_latest_build_epoch = latest_build_epoch.clone();
+        thread::spawn(move || {
+            scene_thread(scene, executor, main_to_worker_receiver, worker_latest_build_epoch)
+        });
+        SceneProxy { sender: main_to_worker_sender, latest_build_epoch }
     }
 
     #[inline]
@@ -57,19 +67,38 @@ impl SceneProxy {
         self.sender.send(MainToWorkerMsg::SetViewBox(new_view_box)).unwrap();
     }
 
+    /// Builds the scene on the worker thread, sending the resulting commands to `listener`.
+    ///
+    /// If another build is requested before this one finishes, this one is abandoned: `listener`
+    /// receives no further commands, including `RenderCommand::Finish`, and is then dropped.
     #[inline]
     pub fn build_with_listener(&self,
                                options: RenderOptions,
                                listener: Box<dyn RenderCommandListener>) {
-        self.sender.send(MainToWorkerMsg::Build(options, listener)).unwrap();
+        self.build(options, listener);
     }
 
+    /// Builds the scene on the worker thread, returning a stream of the resulting commands.
+    ///
+    /// If another build is requested before this one finishes, this one is abandoned, and the
+    /// stream ends early, reporting that it was `superseded()`.
     #[inline]
     pub fn build_with_stream(&self, options: RenderOptions) -> RenderCommandStream {
         let (sender, receiver) = mpsc::sync_channel(MAX_MESSAGES_IN_FLIGHT);
         let listener = Box::new(move |command| sender.send(command).unwrap());
-        self.build_with_listener(options, listener);
-        RenderCommandStream::new(receiver)
+        let superseded = self.build(options, listener);
+        RenderCommandStream::new(receiver, superseded)
+    }
+
+    // Returns a flag that the worker sets before dropping `listener` if the build is abandoned
+    // because a newer one was requested.
+    fn build(&self, options: RenderOptions, listener: Box<dyn RenderCommandListener>)
+             -> Arc<AtomicBool> {
+        let epoch = self.latest_build_epoch.fetch_add(1, Ordering::SeqCst) + 1;
+        let superseded = Arc::new(AtomicBool::new(false));
+        self.sender.send(MainToWorkerMsg::Build(options, listener, epoch, superseded.clone()))
+                   .unwrap();
+        superseded
     }
 
     /// A convenience method to build a scene and send the resulting commands
@@ -99,13 +128,41 @@ impl SceneProxy {
 
 fn scene_thread<E>(mut scene: Scene,
                    executor: E,
-                   main_to_worker_receiver: Receiver<MainToWorkerMsg>)
+                   main_to_worker_receiver: Receiver<MainToWorkerMsg>,
+                   latest_build_epoch: Arc<AtomicU64>)
                    where E: Executor {
     while let Ok(msg) = main_to_worker_receiver.recv() {
         match msg {
             MainToWorkerMsg::ReplaceScene(new_scene) => scene = new_scene,
             MainToWorkerMsg::SetViewBox(new_view_box) => scene.set_view_box(new_view_box),
-            MainToWorkerMsg::Build(options, listener) => scene.build(options, listener, &executor),
+            MainToWorkerMsg::Build(_, _, epoch, superseded)
+                    if epoch != latest_build_epoch.load(Ordering::SeqCst) => {
+                superseded.store(true, Ordering::SeqCst);
+            }
+            MainToWorkerMsg::Build(options, listener, epoch, superseded) => {
+                let listener = Arc::new(EpochListener {
+                    listener,
+                    epoch,
+                    latest_build_epoch: latest_build_epoch.clone(),
+                    finish: Mutex::new(None),
+                });
+
+                // If building panics, the listener is dropped without sending `Finish`, which the
+                // main thread sees as a failed build. Keep this thread alive for later builds.
+                let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                    scene.build(options, Box::new(listener.clone()), &executor)
+                }));
+
+                // A newer build may have been requested while this one was running. In that case,
+                // hold back `Finish` so that the stale build is never presented.
+                if result.is_ok() {
+                    if listener.is_stale() {
+                        superseded.store(true, Ordering::SeqCst);
+                    } else if let Some(finish) = listener.finish.lock().unwrap().take() {
+                        listener.listener.send(finish);
+                    }
+                }
+            }
             MainToWorkerMsg::GetSVG(sender) => {
                 let mut bytes = vec![];
                 scene.write_svg(&mut bytes).unwrap();
@@ -118,18 +175,61 @@ fn scene_thread<E>(mut scene: Scene,
 enum MainToWorkerMsg {
     ReplaceScene(Scene),
     SetViewBox(RectF),
-    Build(RenderOptions, Box<dyn RenderCommandListener>),
+    Build(RenderOptions, Box<dyn RenderCommandListener>, u64, Arc<AtomicBool>),
     GetSVG(Sender<Vec<u8>>),
 }
 
+// Forwards commands from one build until a newer build is requested. `Finish` is held back so
+// that the scene thread can decide whether to send it once the build is done.
+struct EpochListener {
+    listener: Box<dyn RenderCommandListener>,
+    epoch: u64,
+    latest_build_epoch: Arc<AtomicU64>,
+    finish: Mutex<Option<RenderCommand>>,
+}
+
+impl EpochListener {
+    #[inline]
+    fn is_stale(&self) -> bool {
+        self.epoch != self.latest_build_epoch.load(Ordering::SeqCst)
+    }
+}
+
+impl RenderCommandListener for Arc<EpochListener> {
+    fn send(&self, command: RenderCommand) {
+        if let RenderCommand::Finish { .. } = command {
+            *self.finish.lock().unwrap() = Some(command);
+        } else if !self.is_stale() {
+            self.listener.send(command)
+        }
+    }
+}
+
 pub struct RenderCommandStream {
     receiver: Receiver<RenderCommand>,
     done: bool,
+    failed: bool,
+    superseded: Arc<AtomicBool>,
 }
 
 impl RenderCommandStream {
-    fn new(receiver: Receiver<RenderCommand>) -> RenderCommandStream {
-        RenderCommandStream { receiver, done: false }
+    fn new(receiver: Receiver<RenderCommand>, superseded: Arc<AtomicBool>)
+           -> RenderCommandStream {
+        RenderCommandStream { receiver, done: false, failed: false, superseded }
+    }
+
+    /// Returns true if the stream ended before `RenderCommand::Finish` because building the scene
+    /// failed.
+    #[inline]
+    pub fn failed(&self) -> bool {
+        self.failed
+    }
+
+    /// Returns true if the stream ended before `RenderCommand::Finish` because a newer build was
+    /// requested. This is not a failure; the newer build's stream carries the up-to-date scene.
+    #[inline]
+    pub fn superseded(&self) -> bool {
+        self.done && !self.failed && self.superseded.load(Ordering::SeqCst)
     }
 }
 
@@ -141,11 +241,74 @@ impl Iterator for RenderCommandStream {
         if self.done {
             None
         } else {
-            let command = self.receiver.recv().unwrap();
-            if let RenderCommand::Finish { .. } = command {
-                self.done = true;
+            match self.receiver.recv() {
+                Ok(command) => {
+                    if let RenderCommand::Finish { .. } = command {
+                        self.done = true;
+                    }
+                    Some(command)
+                }
+                Err(_) => {
+                    // The scene thread sets the flag before dropping the sender.
+                    self.done = true;
+                    self.failed = !self.superseded.load(Ordering::SeqCst);
+                    None
+                }
             }
-            Some(command)
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::SceneProxy;
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::gpu_data::RenderCommand;
+    use crate::options::{RenderCommandListener, RenderOptions};
+    use std::sync::Mutex;
+    use std::sync::mpsc::{self, Receiver, Sender};
+
+    // Signals that the scene thread is inside a build, then blocks it there until the unblocking
+    // sender is dropped.
+    struct BlockingListener {
+        started: Mutex<Sender<()>>,
+        unblock: Mutex<Receiver<()>>,
+    }
+
+    impl RenderCommandListener for BlockingListener {
+        fn send(&self, _: RenderCommand) {
+            let _ = self.started.lock().unwrap().send(());
+            let _ = self.unblock.lock().unwrap().recv();
+        }
+    }
+
+    #[test]
+    fn test_superseded_build() {
+        let scene_proxy = SceneProxy::new(SequentialExecutor);
+        let (started_sender, started_receiver) = mpsc::channel();
+        let (unblock_sender, unblock_receiver) = mpsc::channel();
+        let listener = BlockingListener {
+            started: Mutex::new(started_sender),
+            unblock: Mutex::new(unblock_receiver),
+        };
+        scene_proxy.build_with_listener(RenderOptions::default(), Box::new(listener));
+        started_receiver.recv().unwrap();
+
+        // Both of these are queued behind the blocked build, so the first never starts.
+        let mut first_stream = scene_proxy.build_with_stream(RenderOptions::default());
+        let mut second_stream = scene_proxy.build_with_stream(RenderOptions::default());
+        drop(unblock_sender);
+
+        assert_eq!(first_stream.by_ref().count(), 0);
+        assert!(first_stream.superseded());
+        assert!(!first_stream.failed());
+
+        let last_command = second_stream.by_ref().last();
+        match last_command {
+            Some(RenderCommand::Finish { .. }) => {}
+            _ => panic!("the latest build should finish"),
+        }
+        assert!(!second_stream.superseded());
+        assert!(!second_stream.failed());
+    }
+}
@@ -18,6 +18,7 @@
 use crate::gpu::renderer::{RenderStats, RenderTime};
 use pathfinder_geometry::basic::vector::Vector2I;
 use pathfinder_geometry::basic::rect::RectI;
+use pathfinder_geometry::color::ColorU;
 use pathfinder_gpu::resources::ResourceLoader;
 use pathfinder_gpu::Device;
 use pathfinder_ui::{FONT_ASCENT, LINE_HEIGHT, PADDING, UIPresenter, WINDOW_COLOR};
@@ -31,7 +32,19 @@ const STATS_WINDOW_WIDTH: i32 = 325;
 const STATS_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
 
 const PERFORMANCE_WINDOW_WIDTH: i32 = 400;
-const PERFORMANCE_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 4 + PADDING + 2;
+const PERFORMANCE_WINDOW_HEIGHT: i32 = LINE_HEIGHT * 5 + PADDING + 2;
+
+const TIMING_GRAPH_WINDOW_WIDTH: i32 = PERFORMANCE_WINDOW_WIDTH;
+const TIMING_GRAPH_HEIGHT: i32 = 96;
+const TIMING_GRAPH_WINDOW_HEIGHT: i32 = LINE_HEIGHT + TIMING_GRAPH_HEIGHT + PADDING * 2;
+// Each sample gets one CPU bar and one GPU bar.
+const TIMING_GRAPH_BAR_WIDTH: i32 =
+    (TIMING_GRAPH_WINDOW_WIDTH - PADDING * 2) / (SAMPLE_BUFFER_SIZE as i32 * 2);
+// The graph never zooms in further than this, so that noise in fast frames doesn't look dramatic.
+const TIMING_GRAPH_MIN_SCALE_MS: f64 = 1.0;
+
+static CPU_GRAPH_COLOR: ColorU = ColorU { r: 64,  g: 160, b: 255, a: 255 };
+static GPU_GRAPH_COLOR: ColorU = ColorU { r: 255, g: 160, b: 64,  a: 255 };
 
 pub struct DebugUIPresenter<D>
 where
@@ -41,6 +54,7 @@ where
 
     cpu_samples: SampleBuffer<CPUSample>,
     gpu_samples: SampleBuffer<GPUSample>,
+    timing_samples: VecDeque<TimingSample>,
 }
 
 impl<D> DebugUIPresenter<D>
@@ -57,6 +71,7 @@ where
             ui_presenter,
             cpu_samples: SampleBuffer::new(),
             gpu_samples: SampleBuffer::new(),
+            timing_samples: VecDeque::with_capacity(SAMPLE_BUFFER_SIZE),
         }
     }
 
@@ -65,20 +80,31 @@ where
         stats: RenderStats,
         tile_time: Duration,
         rendering_time: Option<RenderTime>,
+        present_time: Duration,
     ) {
         self.cpu_samples.push(CPUSample {
             stats,
             elapsed: tile_time,
+            present_time,
         });
         if let Some(time) = rendering_time {
             self.gpu_samples.push(GPUSample { time })
         }
+
+        self.timing_samples.push_back(TimingSample {
+            cpu_time: tile_time,
+            gpu_time: rendering_time,
+        });
+        while self.timing_samples.len() > SAMPLE_BUFFER_SIZE {
+            self.timing_samples.pop_front();
+        }
     }
 
     pub fn draw(&self, device: &D) {
         let mean_cpu_sample = self.cpu_samples.mean();
         self.draw_stats_window(device, &mean_cpu_sample);
         self.draw_performance_window(device, &mean_cpu_sample);
+        self.draw_timing_graph_window(device);
     }
 
     fn draw_stats_window(&self, device: &D, mean_cpu_sample: &CPUSample) {
@@ -174,6 +200,98 @@ where
             origin + Vector2I::new(0, LINE_HEIGHT * 3),
             false,
         );
+        self.ui_presenter.draw_text(
+            device,
+            &format!("Present: {:.3} ms", duration_to_ms(mean_cpu_sample.present_time)),
+            origin + Vector2I::new(0, LINE_HEIGHT * 4),
+            false,
+        );
+    }
+
+    fn draw_timing_graph_window(&self, device: &D) {
+        let framebuffer_size = self.ui_presenter.framebuffer_size();
+        let bottom = framebuffer_size.y() - PADDING;
+        let window_rect = RectI::new(
+            Vector2I::new(
+                framebuffer_size.x() - PADDING - TIMING_GRAPH_WINDOW_WIDTH,
+                bottom - PERFORMANCE_WINDOW_HEIGHT - PADDING - STATS_WINDOW_HEIGHT - PADDING -
+                    TIMING_GRAPH_WINDOW_HEIGHT,
+            ),
+            Vector2I::new(TIMING_GRAPH_WINDOW_WIDTH, TIMING_GRAPH_WINDOW_HEIGHT),
+        );
+
+        self.ui_presenter.draw_solid_rounded_rect(device, window_rect, WINDOW_COLOR);
+
+        let cpu_times: Vec<Option<f64>> = self.timing_samples
+                                              .iter()
+                                              .map(|sample| Some(duration_to_ms(sample.cpu_time)))
+                                              .collect();
+        let gpu_times: Vec<Option<f64>> = self.timing_samples
+                                              .iter()
+                                              .map(|sample| {
+                                                  sample.gpu_time.map(|time| {
+                                                      duration_to_ms(time.stage_0 + time.stage_1)
+                                                  })
+                                              })
+                                              .collect();
+        let max_time = cpu_times.iter()
+                                .chain(gpu_times.iter())
+                                .filter_map(|&time| time)
+                                .fold(TIMING_GRAPH_MIN_SCALE_MS, f64::max);
+
+        // Draw the legend.
+        let origin = window_rect.origin() + Vector2I::new(PADDING, PADDING + FONT_ASCENT);
+        let mut legend_x = origin.x();
+        for &(label, color) in &[("CPU", CPU_GRAPH_COLOR), ("GPU", GPU_GRAPH_COLOR)] {
+            let swatch_rect = RectI::new(Vector2I::new(legend_x, origin.y() - FONT_ASCENT),
+                                         Vector2I::splat(FONT_ASCENT));
+            self.ui_presenter.draw_solid_rect(device, swatch_rect, color);
+            legend_x += FONT_ASCENT + PADDING / 2;
+            self.ui_presenter.draw_text(device, label, Vector2I::new(legend_x, origin.y()), false);
+            legend_x += self.ui_presenter.measure_text(label) + PADDING;
+        }
+        let scale_label = format!("{:.1} ms", max_time);
+        let scale_label_x =
+            window_rect.max_x() - PADDING - self.ui_presenter.measure_text(&scale_label);
+        self.ui_presenter.draw_text(device,
+                                    &scale_label,
+                                    Vector2I::new(scale_label_x, origin.y()),
+                                    false);
+
+        // Draw the bars, with the most recent sample on the right. A frame without a GPU time
+        // leaves its GPU slot empty.
+        let graph_rect = RectI::from_points(
+            window_rect.lower_left() + Vector2I::new(PADDING, -PADDING - TIMING_GRAPH_HEIGHT),
+            window_rect.lower_right() - Vector2I::splat(PADDING),
+        );
+        self.draw_timing_graph_bars(device, graph_rect, &cpu_times, max_time, 0, CPU_GRAPH_COLOR);
+        self.draw_timing_graph_bars(device, graph_rect, &gpu_times, max_time, 1, GPU_GRAPH_COLOR);
+    }
+
+    fn draw_timing_graph_bars(&self,
+                              device: &D,
+                              graph_rect: RectI,
+                              times: &[Option<f64>],
+                              max_time: f64,
+                              series_index: i32,
+                              color: ColorU) {
+        for (age, &time) in times.iter().rev().enumerate() {
+            let time = match time {
+                None => continue,
+                Some(time) => time,
+            };
+            let height = (time / max_time * graph_rect.size().y() as f64).round() as i32;
+            if height == 0 {
+                continue;
+            }
+            let slot_x = graph_rect.max_x() - TIMING_GRAPH_BAR_WIDTH * 2 * (age as i32 + 1);
+            let bar_rect = RectI::new(
+                Vector2I::new(slot_x + TIMING_GRAPH_BAR_WIDTH * series_index,
+                              graph_rect.max_y() - height),
+                Vector2I::new(TIMING_GRAPH_BAR_WIDTH, height),
+            );
+            self.ui_presenter.draw_solid_rect(device, bar_rect, color);
+        }
     }
 
 }
@@ -220,6 +338,8 @@ where
 struct CPUSample {
     elapsed: Duration,
     stats: RenderStats,
+    // Time spent presenting the frame, which includes any wait for vsync.
+    present_time: Duration,
 }
 
 impl Add<CPUSample> for CPUSample {
@@ -228,6 +348,7 @@ impl Add<CPUSample> for CPUSample {
         CPUSample {
             elapsed: self.elapsed + other.elapsed,
             stats: self.stats + other.stats,
+            present_time: self.present_time + other.present_time,
         }
     }
 }
@@ -238,10 +359,18 @@ impl Div<usize> for CPUSample {
         CPUSample {
             elapsed: self.elapsed / (divisor as u32),
             stats: self.stats / divisor,
+            present_time: self.present_time / (divisor as u32),
         }
     }
 }
 
+// The CPU and GPU times of one frame, for the timing graph. The GPU time is `None` if no timer
+// query result came back during the frame.
+struct TimingSample {
+    cpu_time: Duration,
+    gpu_time: Option<RenderTime>,
+}
+
 #[derive(Clone, Default)]
 struct GPUSample {
     time: RenderTime,
@@ -97,6 +97,7 @@ where
     // Extra info
     render_mode: RenderMode,
     use_depth: bool,
+    depth_func: DepthFunc,
 }
 
 impl<D> Renderer<D>
@@ -232,6 +233,7 @@ where
 
             render_mode: RenderMode::default(),
             use_depth: false,
+            depth_func: DepthFunc::default(),
         };
 
         // As a convenience, bind the destination framebuffer.
@@ -244,6 +246,7 @@ where
         self.init_postprocessing_framebuffer();
 
         self.mask_framebuffer_cleared = false;
+        self.buffered_fills.clear();
         self.stats = RenderStats::default();
     }
 
@@ -353,6 +356,12 @@ where
         self.use_depth = true;
     }
 
+    /// Sets the depth comparison function used when drawing the scene with depth enabled.
+    #[inline]
+    pub fn set_depth_func(&mut self, depth_func: DepthFunc) {
+        self.depth_func = depth_func;
+    }
+
     #[inline]
     pub fn quad_vertex_positions_buffer(&self) -> &D::Buffer {
         &self.quad_vertex_positions_buffer
@@ -746,7 +755,7 @@ where
             &RenderState {
                 // FIXME(pcwalton): Should we really write to the depth buffer?
                 depth: Some(DepthState {
-                    func: DepthFunc::Less,
+                    func: self.depth_func,
                     write: true,
                 }),
                 stencil: Some(StencilState {
@@ -790,7 +799,7 @@ where
             &RenderState {
                 blend: BlendState::RGBSrcAlphaAlphaOneMinusSrcAlpha,
                 depth: Some(DepthState {
-                    func: DepthFunc::Less,
+                    func: self.depth_func,
                     write: false,
                 }),
                 ..RenderState::default()
@@ -20,9 +20,9 @@ pub mod options;
 pub mod paint;
 pub mod post;
 pub mod scene;
+pub mod tile_map;
 
 mod builder;
 mod sorted_vector;
-mod tile_map;
 mod tiles;
 mod z_buffer;
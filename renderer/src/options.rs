@@ -36,6 +36,9 @@ pub struct RenderOptions {
     pub transform: RenderTransform,
     pub dilation: Vector2F,
     pub subpixel_aa_enabled: bool,
+    // Paths smaller than this many device pixels in both dimensions are skipped. Only applies to
+    // 2D transforms. Zero disables culling.
+    pub lod_threshold: f32,
 }
 
 impl RenderOptions {
@@ -44,6 +47,7 @@ impl RenderOptions {
             transform: self.transform.prepare(bounds),
             dilation: self.dilation,
             subpixel_aa_enabled: self.subpixel_aa_enabled,
+            lod_threshold: self.lod_threshold,
         }
     }
 }
@@ -123,6 +127,7 @@ pub(crate) struct PreparedRenderOptions {
     pub(crate) transform: PreparedRenderTransform,
     pub(crate) dilation: Vector2F,
     pub(crate) subpixel_aa_enabled: bool,
+    pub(crate) lod_threshold: f32,
 }
 
 impl PreparedRenderOptions {
@@ -133,6 +138,22 @@ impl PreparedRenderOptions {
             _ => [Vector4F::default(); 4],
         }
     }
+
+    // Returns true if a path with the given bounds is too small on screen to be worth tiling.
+    #[inline]
+    pub(crate) fn culls_path_with_bounds(&self, bounds: RectF) -> bool {
+        if self.lod_threshold <= 0.0 {
+            return false;
+        }
+        let bounds = match self.transform {
+            PreparedRenderTransform::None => bounds,
+            PreparedRenderTransform::Transform2D(ref transform) => {
+                transform.transform_rect(&bounds)
+            }
+            PreparedRenderTransform::Perspective { .. } => return false,
+        };
+        bounds.size().x() < self.lod_threshold && bounds.size().y() < self.lod_threshold
+    }
 }
 
 pub(crate) type BoundingQuad = [Vector4F; 4];
@@ -15,6 +15,7 @@ use crate::concurrent::executor::Executor;
 use crate::options::{PreparedRenderOptions, PreparedRenderTransform};
 use crate::options::{RenderCommandListener, RenderOptions};
 use crate::paint::{Paint, PaintId};
+use crate::tile_map::DenseTileMap;
 use hashbrown::HashMap;
 use pathfinder_geometry::basic::vector::Vector2F;
 use pathfinder_geometry::basic::rect::RectF;
@@ -76,6 +77,26 @@ impl Scene {
         self.bounds = new_bounds;
     }
 
+    /// Returns the tight bounding box of the outlines of every path in this scene, or an empty
+    /// rect if there are no paths.
+    ///
+    /// Unlike `bounds()`, this is computed from scratch, so it isn't affected by `set_bounds()`.
+    pub fn object_bounds(&self) -> RectF {
+        let mut paths = self.paths.iter();
+        match paths.next() {
+            None => RectF::default(),
+            Some(first_path) => paths.fold(first_path.outline.bounds(), |bounds, path| {
+                bounds.union_rect(path.outline.bounds())
+            }),
+        }
+    }
+
+    /// Resets the bounds of this scene to `object_bounds()`.
+    #[inline]
+    pub fn recompute_bounds(&mut self) {
+        self.bounds = self.object_bounds();
+    }
+
     #[inline]
     pub fn view_box(&self) -> RectF {
         self.view_box
@@ -174,30 +195,73 @@ impl Scene {
         SceneBuilder::new(self, &prepared_options, listener).build(executor)
     }
 
+    /// Like `build()`, but also returns the result of occlusion culling: for each tile, the index
+    /// of the frontmost path that completely covers it, or `None` if no path does. Paths behind
+    /// that one are culled from the tile.
+    ///
+    /// This is a debugging aid for paths that go missing, particularly in 3D.
+    pub fn build_with_occluders<E>(&self,
+                                   options: RenderOptions,
+                                   listener: Box<dyn RenderCommandListener>,
+                                   executor: &E)
+                                   -> DenseTileMap<Option<u32>>
+                                   where E: Executor {
+        let prepared_options = options.prepare(self.bounds);
+        let mut scene_builder = SceneBuilder::new(self, &prepared_options, listener);
+        scene_builder.build(executor);
+        scene_builder.z_buffer.occluders()
+    }
+
+    /// Writes this scene out as an SVG document with one filled path per path object.
+    ///
+    /// If every path has the same color, the fill is set once on the root element.
     pub fn write_svg<W>(&self, writer: &mut W) -> io::Result<()> where W: Write {
-        writeln!(
+        write!(
             writer,
-            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\">",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"{} {} {} {}\"",
             self.view_box.origin().x(),
             self.view_box.origin().y(),
             self.view_box.size().x(),
             self.view_box.size().y()
         )?;
+        let monochrome_color = self.monochrome_color();
+        if let Some(color) = monochrome_color {
+            write_svg_fill(writer, color)?;
+        }
+        writeln!(writer, ">")?;
+
         for path_object in &self.paths {
-            let paint = &self.paints[path_object.paint.0 as usize];
             write!(writer, "    <path")?;
             if !path_object.name.is_empty() {
-                write!(writer, " id=\"{}\"", path_object.name)?;
+                write!(writer, " id=\"{}\"", escape_svg_attribute(&path_object.name))?;
+            }
+            if monochrome_color.is_none() {
+                write_svg_fill(writer, self.paints[path_object.paint.0 as usize].color)?;
             }
-            writeln!(
-                writer,
-                " fill=\"{:?}\" d=\"{:?}\" />",
-                paint.color, path_object.outline
-            )?;
+            writeln!(writer, " d=\"{:?}\" />", path_object.outline)?;
         }
         writeln!(writer, "</svg>")?;
         Ok(())
     }
+
+    /// Returns this scene as an SVG document. See `write_svg()`.
+    pub fn to_svg(&self) -> String {
+        let mut bytes = vec![];
+        self.write_svg(&mut bytes).unwrap();
+        String::from_utf8(bytes).unwrap()
+    }
+}
+
+fn write_svg_fill<W>(writer: &mut W, color: ColorU) -> io::Result<()> where W: Write {
+    write!(writer, " fill=\"#{:02x}{:02x}{:02x}\"", color.r, color.g, color.b)?;
+    if color.a != 255 {
+        write!(writer, " fill-opacity=\"{}\"", color.a as f32 / 255.0)?;
+    }
+    Ok(())
+}
+
+fn escape_svg_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
 }
 
 #[derive(Clone, Debug)]
@@ -223,3 +287,111 @@ impl PathObject {
         self.paint
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::concurrent::executor::SequentialExecutor;
+    use crate::options::RenderOptions;
+    use crate::paint::Paint;
+    use crate::scene::{PathObject, Scene};
+    use crate::tile_map::TileMap;
+    use pathfinder_geometry::basic::rect::RectF;
+    use pathfinder_geometry::basic::vector::{Vector2F, Vector2I};
+    use pathfinder_geometry::color::ColorU;
+    use pathfinder_geometry::outline::{Contour, Outline};
+
+    fn triangle(offset: f32) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(offset, 0.0));
+        contour.push_endpoint(Vector2F::new(offset + 10.0, 0.0));
+        contour.push_endpoint(Vector2F::new(offset, 10.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    fn square(origin: f32, size: f32) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::splat(origin));
+        contour.push_endpoint(Vector2F::new(origin + size, origin));
+        contour.push_endpoint(Vector2F::splat(origin + size));
+        contour.push_endpoint(Vector2F::new(origin, origin + size));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    #[test]
+    fn test_build_with_occluders() {
+        // An opaque square over the whole view box, and a smaller one on top. Only tiles that no
+        // edge passes through are solid, and thus can occlude.
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::splat(64.0)));
+        let black = scene.push_paint(&Paint { color: ColorU::black() });
+        scene.push_path(PathObject::new(square(0.0, 64.0), black, String::new()));
+        scene.push_path(PathObject::new(square(8.0, 48.0), black, String::new()));
+        scene.recompute_bounds();
+
+        let occluders = scene.build_with_occluders(RenderOptions::default(),
+                                                   Box::new(|_| {}),
+                                                   &SequentialExecutor);
+        assert_eq!(occluders.rect.size(), Vector2I::splat(4));
+        assert_eq!(occluders.data[occluders.coords_to_index(Vector2I::new(0, 0)).unwrap()],
+                   None);
+        assert_eq!(occluders.data[occluders.coords_to_index(Vector2I::new(0, 1)).unwrap()],
+                   Some(0));
+        assert_eq!(occluders.data[occluders.coords_to_index(Vector2I::new(1, 1)).unwrap()],
+                   Some(1));
+        assert_eq!(occluders.data[occluders.coords_to_index(Vector2I::new(2, 2)).unwrap()],
+                   Some(1));
+        assert_eq!(occluders.data[occluders.coords_to_index(Vector2I::new(3, 2)).unwrap()],
+                   Some(0));
+    }
+
+    #[test]
+    fn test_recompute_bounds() {
+        let mut scene = Scene::new();
+        let paint = scene.push_paint(&Paint { color: ColorU::black() });
+        scene.push_path(PathObject::new(triangle(20.0), paint, String::new()));
+        scene.set_bounds(RectF::new(Vector2F::default(), Vector2F::splat(5.0)));
+        scene.push_path(PathObject::new(triangle(100.0), paint, String::new()));
+
+        let expected = RectF::from_points(Vector2F::new(20.0, 0.0), Vector2F::new(110.0, 10.0));
+        assert_eq!(scene.object_bounds(), expected);
+        assert_ne!(scene.bounds(), expected);
+        scene.recompute_bounds();
+        assert_eq!(scene.bounds(), expected);
+    }
+
+    #[test]
+    fn test_to_svg() {
+        let mut scene = Scene::new();
+        scene.set_view_box(RectF::new(Vector2F::default(), Vector2F::new(100.0, 50.0)));
+        let red = scene.push_paint(&Paint { color: ColorU { r: 255, g: 0, b: 0, a: 255 } });
+        let blue = scene.push_paint(&Paint { color: ColorU { r: 0, g: 0, b: 255, a: 51 } });
+        scene.push_path(PathObject::new(triangle(0.0), red, "a<b".to_owned()));
+        scene.push_path(PathObject::new(triangle(20.0), blue, String::new()));
+
+        let svg = scene.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" \
+                                 viewBox=\"0 0 100 50\">\n"));
+        assert!(svg.contains("<path id=\"a&lt;b\" fill=\"#ff0000\" d=\"M 0 0 L 10 0 L 0 10"));
+        assert!(svg.contains("<path fill=\"#0000ff\" fill-opacity=\"0.2\" d=\"M 20 0"));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+
+    #[test]
+    fn test_to_svg_monochrome() {
+        let mut scene = Scene::new();
+        let red = scene.push_paint(&Paint { color: ColorU { r: 255, g: 0, b: 0, a: 255 } });
+        scene.push_path(PathObject::new(triangle(0.0), red, String::new()));
+        scene.push_path(PathObject::new(triangle(20.0), red, String::new()));
+
+        let svg = scene.to_svg();
+        assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" \
+                                 viewBox=\"0 0 0 0\" fill=\"#ff0000\">\n"));
+        assert_eq!(svg.matches("fill=").count(), 1);
+    }
+}
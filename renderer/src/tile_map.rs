@@ -8,8 +8,65 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use hashbrown::HashMap;
 use pathfinder_geometry::basic::vector::Vector2I;
 use pathfinder_geometry::basic::rect::RectI;
+#[cfg(feature = "serde")]
+use serde::de::Error as DeserializeError;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::slice::Iter as SliceIter;
+use std::vec::IntoIter as VecIntoIter;
+
+const NEIGHBOR_OFFSETS_4: [(i32, i32); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+const NEIGHBOR_OFFSETS_8: [(i32, i32); 8] = [
+    (-1, -1), (0, -1), (1, -1),
+    (-1, 0), (1, 0),
+    (-1, 1), (0, 1), (1, 1),
+];
+
+/// Maps between tile coordinates and linear tile indices within a rectangle of tiles.
+pub trait TileMap {
+    fn rect(&self) -> RectI;
+
+    #[inline]
+    fn coords_to_index(&self, coords: Vector2I) -> Option<usize> {
+        // TODO(pcwalton): SIMD?
+        let rect = self.rect();
+        if coords.x() < rect.min_x()
+            || coords.x() >= rect.max_x()
+            || coords.y() < rect.min_y()
+            || coords.y() >= rect.max_y()
+        {
+            return None;
+        }
+        Some(self.coords_to_index_unchecked(coords))
+    }
+
+    /// Replaces the contents of `indices` with the result of `coords_to_index` for each of
+    /// `coords`.
+    #[inline]
+    fn coords_to_indices(&self, coords: &[Vector2I], indices: &mut Vec<Option<usize>>) {
+        indices.clear();
+        indices.extend(coords.iter().map(|&coords| self.coords_to_index(coords)));
+    }
+
+    #[inline]
+    fn coords_to_index_unchecked(&self, coords: Vector2I) -> usize {
+        let rect = self.rect();
+        (coords.y() - rect.min_y()) as usize * rect.size().x() as usize
+            + (coords.x() - rect.min_x()) as usize
+    }
+
+    #[inline]
+    fn index_to_coords(&self, index: usize) -> Vector2I {
+        let rect = self.rect();
+        let (width, index) = (rect.size().x(), index as i32);
+        rect.origin() + Vector2I::new(index % width, index / width)
+    }
+}
 
 #[derive(Debug)]
 pub struct DenseTileMap<T> {
@@ -43,27 +100,379 @@ impl<T> DenseTileMap<T> {
     }
 
     #[inline]
-    pub fn coords_to_index(&self, coords: Vector2I) -> Option<usize> {
-        // TODO(pcwalton): SIMD?
-        if coords.x() < self.rect.min_x()
-            || coords.x() >= self.rect.max_x()
-            || coords.y() < self.rect.min_y()
-            || coords.y() >= self.rect.max_y()
-        {
-            return None;
+    pub fn from_data(data: Vec<T>, rect: RectI) -> DenseTileMap<T> {
+        debug_assert_eq!(data.len(), rect.size().x() as usize * rect.size().y() as usize);
+        DenseTileMap { data, rect }
+    }
+
+    /// Returns a map over the same rect with `f` applied to each tile.
+    #[inline]
+    pub fn map<U, F>(&self, f: F) -> DenseTileMap<U> where F: FnMut(&T) -> U {
+        DenseTileMap { data: self.data.iter().map(f).collect(), rect: self.rect }
+    }
+
+    /// Like `map`, but also passes the coordinates of each tile to `f`.
+    #[inline]
+    pub fn map_with_coords<U, F>(&self, mut f: F) -> DenseTileMap<U>
+                                 where F: FnMut(Vector2I, &T) -> U {
+        let data = self.data
+                       .iter()
+                       .enumerate()
+                       .map(|(index, tile)| f(self.index_to_coords(index), tile))
+                       .collect();
+        DenseTileMap { data, rect: self.rect }
+    }
+
+    /// Sets every tile in `rect` to `value`.
+    ///
+    /// The rect is clamped to the bounds of this map; if they don't overlap, this is a no-op.
+    pub fn fill_rect(&mut self, rect: RectI, value: T) where T: Clone {
+        let min_x = i32::max(rect.min_x(), self.rect.min_x());
+        let min_y = i32::max(rect.min_y(), self.rect.min_y());
+        let max_x = i32::min(rect.max_x(), self.rect.max_x());
+        let max_y = i32::min(rect.max_y(), self.rect.max_y());
+        if min_x >= max_x || min_y >= max_y {
+            return;
         }
-        Some(self.coords_to_index_unchecked(coords))
+
+        let row_length = (max_x - min_x) as usize;
+        for y in min_y..max_y {
+            let row_start = self.coords_to_index_unchecked(Vector2I::new(min_x, y));
+            for tile in &mut self.data[row_start..(row_start + row_length)] {
+                *tile = value.clone();
+            }
+        }
+    }
+
+    /// Returns a map over `new_rect` that keeps the tiles of this map that lie within it.
+    ///
+    /// Tiles outside this map's rect are set to the default value.
+    pub fn resized_to(&self, new_rect: RectI) -> DenseTileMap<T> where T: Copy + Clone + Default {
+        let mut new_map = DenseTileMap::new(new_rect);
+        let min_x = i32::max(new_rect.min_x(), self.rect.min_x());
+        let min_y = i32::max(new_rect.min_y(), self.rect.min_y());
+        let max_x = i32::min(new_rect.max_x(), self.rect.max_x());
+        let max_y = i32::min(new_rect.max_y(), self.rect.max_y());
+        if min_x >= max_x || min_y >= max_y {
+            return new_map;
+        }
+
+        let row_length = (max_x - min_x) as usize;
+        for y in min_y..max_y {
+            let old_start = self.coords_to_index_unchecked(Vector2I::new(min_x, y));
+            let new_start = new_map.coords_to_index_unchecked(Vector2I::new(min_x, y));
+            new_map.data[new_start..(new_start + row_length)]
+                   .copy_from_slice(&self.data[old_start..(old_start + row_length)]);
+        }
+        new_map
+    }
+
+    /// Returns the 4-connected neighbors of the tile at `coords` that lie within this map.
+    #[inline]
+    pub fn neighbors4<'a>(&'a self, coords: Vector2I)
+                          -> impl Iterator<Item = (Vector2I, &'a T)> + 'a {
+        self.neighbors(coords, &NEIGHBOR_OFFSETS_4)
+    }
+
+    /// Returns the 8-connected neighbors of the tile at `coords` that lie within this map.
+    #[inline]
+    pub fn neighbors8<'a>(&'a self, coords: Vector2I)
+                          -> impl Iterator<Item = (Vector2I, &'a T)> + 'a {
+        self.neighbors(coords, &NEIGHBOR_OFFSETS_8)
+    }
+
+    #[inline]
+    fn neighbors<'a>(&'a self, coords: Vector2I, offsets: &'static [(i32, i32)])
+                     -> impl Iterator<Item = (Vector2I, &'a T)> + 'a {
+        offsets.iter().filter_map(move |&(x, y)| {
+            let neighbor_coords = coords + Vector2I::new(x, y);
+            self.coords_to_index(neighbor_coords).map(|index| (neighbor_coords, &self.data[index]))
+        })
+    }
+}
+
+impl DenseTileMap<u32> {
+    /// Counts how many times each tile in `rect` occurs in `coords`. Coordinates outside `rect`
+    /// are ignored.
+    ///
+    /// This is useful for visualizing overdraw, by passing in the tile coordinates of each fill.
+    pub fn histogram<I>(rect: RectI, coords: I) -> DenseTileMap<u32>
+                        where I: IntoIterator<Item = Vector2I> {
+        let mut histogram = DenseTileMap::new(rect);
+        for coords in coords {
+            if let Some(index) = histogram.coords_to_index(coords) {
+                histogram.data[index] += 1;
+            }
+        }
+        histogram
+    }
+}
+
+impl<T> IntoIterator for DenseTileMap<T> {
+    type Item = T;
+    type IntoIter = VecIntoIter<T>;
+
+    /// Yields the tiles in row-major order.
+    #[inline]
+    fn into_iter(self) -> VecIntoIter<T> {
+        self.data.into_iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a DenseTileMap<T> {
+    type Item = &'a T;
+    type IntoIter = SliceIter<'a, T>;
+
+    /// Yields the tiles in row-major order.
+    #[inline]
+    fn into_iter(self) -> SliceIter<'a, T> {
+        self.data.iter()
     }
+}
 
+impl<T> TileMap for DenseTileMap<T> {
     #[inline]
-    pub fn coords_to_index_unchecked(&self, coords: Vector2I) -> usize {
-        (coords.y() - self.rect.min_y()) as usize * self.rect.size().x() as usize
-            + (coords.x() - self.rect.min_x()) as usize
+    fn rect(&self) -> RectI {
+        self.rect
+    }
+}
+
+// The rect is serialized as `[origin x, origin y, width, height]`.
+#[cfg(feature = "serde")]
+impl<T> Serialize for DenseTileMap<T> where T: Serialize {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error> where S: Serializer {
+        let (origin, size) = (self.rect.origin(), self.rect.size());
+        let mut state = serializer.serialize_struct("DenseTileMap", 2)?;
+        state.serialize_field("rect", &[origin.x(), origin.y(), size.x(), size.y()])?;
+        state.serialize_field("data", &self.data)?;
+        state.end()
+    }
+}
+
+/// Fails if the number of tiles doesn't match the size of the rect, since indexing such a map
+/// would go out of bounds.
+#[cfg(feature = "serde")]
+impl<'de, T> Deserialize<'de> for DenseTileMap<T> where T: Deserialize<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<DenseTileMap<T>, D::Error>
+                      where D: Deserializer<'de> {
+        #[derive(Deserialize)]
+        #[serde(rename = "DenseTileMap")]
+        struct SerializedDenseTileMap<T> {
+            rect: [i32; 4],
+            data: Vec<T>,
+        }
+
+        let SerializedDenseTileMap { rect, data } = Deserialize::deserialize(deserializer)?;
+        if rect[2] < 0 || rect[3] < 0 {
+            return Err(D::Error::custom(format!("tile map rect has negative size {:?}", rect)));
+        }
+        let length = rect[2] as usize * rect[3] as usize;
+        if data.len() != length {
+            return Err(D::Error::custom(format!("tile map has {} tiles, but its rect has {}",
+                                                data.len(),
+                                                length)));
+        }
+
+        let rect = RectI::new(Vector2I::new(rect[0], rect[1]), Vector2I::new(rect[2], rect[3]));
+        Ok(DenseTileMap { data, rect })
     }
+}
 
+/// A tile map that only stores tiles that have been explicitly set.
+///
+/// This is useful when only a small fraction of the tiles in a large rect are interesting.
+#[derive(Debug)]
+pub struct SparseTileMap<T> {
+    pub data: HashMap<usize, T>,
+    pub rect: RectI,
+}
+
+impl<T> SparseTileMap<T> {
+    #[inline]
+    pub fn new(rect: RectI) -> SparseTileMap<T> {
+        SparseTileMap { data: HashMap::new(), rect }
+    }
+
+    /// Returns the tile at `coords`, or the default value if that tile has not been set.
+    ///
+    /// Returns `None` if the coordinates are out of bounds.
     #[inline]
-    pub fn index_to_coords(&self, index: usize) -> Vector2I {
-        let (width, index) = (self.rect.size().x(), index as i32);
-        self.rect.origin() + Vector2I::new(index % width, index / width)
+    pub fn get(&self, coords: Vector2I) -> Option<T> where T: Clone + Default {
+        let index = self.coords_to_index(coords)?;
+        Some(self.data.get(&index).cloned().unwrap_or_default())
+    }
+
+    /// Sets the tile at `coords`, returning the previous value if there was one.
+    ///
+    /// Panics if the coordinates are out of bounds.
+    #[inline]
+    pub fn insert(&mut self, coords: Vector2I, value: T) -> Option<T> {
+        let index = self.coords_to_index(coords).expect("Tile coordinates out of bounds!");
+        self.data.insert(index, value)
+    }
+
+    /// Iterates over the tiles that have been set, in no particular order.
+    #[inline]
+    pub fn iter<'a>(&'a self) -> impl Iterator<Item = (Vector2I, &'a T)> + 'a {
+        self.data.iter().map(move |(&index, tile)| (self.index_to_coords(index), tile))
+    }
+}
+
+impl<T> TileMap for SparseTileMap<T> {
+    #[inline]
+    fn rect(&self) -> RectI {
+        self.rect
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::tile_map::{DenseTileMap, SparseTileMap, TileMap};
+    use pathfinder_geometry::basic::rect::RectI;
+    use pathfinder_geometry::basic::vector::Vector2I;
+
+    fn test_map() -> DenseTileMap<i32> {
+        let rect = RectI::new(Vector2I::new(-1, -1), Vector2I::new(3, 3));
+        DenseTileMap::from_builder(|index| index as i32, rect)
+    }
+
+    #[test]
+    fn test_neighbors4() {
+        let map = test_map();
+        let interior: Vec<_> = map.neighbors4(Vector2I::new(0, 0)).map(|(_, &t)| t).collect();
+        assert_eq!(interior, vec![1, 3, 5, 7]);
+        assert_eq!(map.neighbors4(Vector2I::new(-1, -1)).count(), 2);
+        assert_eq!(map.neighbors4(Vector2I::new(0, 1)).count(), 3);
+    }
+
+    #[test]
+    fn test_neighbors8() {
+        let map = test_map();
+        assert_eq!(map.neighbors8(Vector2I::new(0, 0)).count(), 8);
+        assert_eq!(map.neighbors8(Vector2I::new(1, 1)).count(), 3);
+        assert_eq!(map.neighbors8(Vector2I::new(-1, 0)).count(), 5);
+        for (coords, &tile) in map.neighbors8(Vector2I::new(1, -1)) {
+            assert_eq!(map.coords_to_index(coords), Some(tile as usize));
+        }
+    }
+
+    #[test]
+    fn test_sparse_tile_map() {
+        let rect = RectI::new(Vector2I::new(-1, -1), Vector2I::new(3, 3));
+        let mut map = SparseTileMap::new(rect);
+        assert_eq!(map.insert(Vector2I::new(1, 0), 7), None);
+        assert_eq!(map.insert(Vector2I::new(1, 0), 8), Some(7));
+        assert_eq!(map.get(Vector2I::new(1, 0)), Some(8));
+        assert_eq!(map.get(Vector2I::new(0, 0)), Some(0));
+        assert_eq!(map.get(Vector2I::new(2, 0)), None);
+        assert_eq!(map.coords_to_index(Vector2I::new(1, 0)),
+                   test_map().coords_to_index(Vector2I::new(1, 0)));
+        let tiles: Vec<_> = map.iter().map(|(coords, &tile)| (coords, tile)).collect();
+        assert_eq!(tiles, vec![(Vector2I::new(1, 0), 8)]);
+    }
+
+    #[test]
+    fn test_map_tiles() {
+        let map = test_map();
+        let doubled = map.map(|&tile| tile * 2);
+        assert_eq!(doubled.rect, map.rect);
+        assert_eq!(doubled.data.len(), map.data.len());
+        assert_eq!(doubled.data, (0..9).map(|tile| tile * 2).collect::<Vec<_>>());
+
+        let coords = map.map_with_coords(|coords, _| coords);
+        assert_eq!(coords.rect, map.rect);
+        assert_eq!(coords.data.len(), map.data.len());
+        for (index, &tile_coords) in coords.data.iter().enumerate() {
+            assert_eq!(map.coords_to_index(tile_coords), Some(index));
+        }
+    }
+
+    #[test]
+    fn test_resized_to() {
+        let rect = RectI::new(Vector2I::new(0, 0), Vector2I::new(4, 4));
+        let map = DenseTileMap::from_builder(|index| index as i32 + 1, rect);
+        let new_rect = RectI::new(Vector2I::new(-1, -1), Vector2I::new(6, 6));
+        let resized = map.resized_to(new_rect);
+        assert_eq!(resized.rect, new_rect);
+        for (index, &tile) in resized.data.iter().enumerate() {
+            let coords = resized.index_to_coords(index);
+            match map.coords_to_index(coords) {
+                Some(old_index) => assert_eq!(tile, map.data[old_index]),
+                None => assert_eq!(tile, 0),
+            }
+        }
+
+        let shrunk = resized.resized_to(RectI::new(Vector2I::new(2, 2), Vector2I::new(4, 4)));
+        assert_eq!(shrunk.data[0], map.data[map.coords_to_index(Vector2I::new(2, 2)).unwrap()]);
+        assert_eq!(shrunk.data[15], 0);
+    }
+
+    #[test]
+    fn test_coords_to_indices() {
+        let map = test_map();
+        let coords: Vec<_> = (-3..4).flat_map(|y| (-3..4).map(move |x| Vector2I::new(x, y)))
+                                    .collect();
+        let mut indices = vec![Some(99)];
+        for count in &[0, 1, 2, 5, coords.len()] {
+            map.coords_to_indices(&coords[0..*count], &mut indices);
+            let expected: Vec<_> = coords[0..*count].iter()
+                                                    .map(|&coords| map.coords_to_index(coords))
+                                                    .collect();
+            assert_eq!(indices, expected);
+        }
+    }
+
+    #[test]
+    fn test_histogram() {
+        let rect = RectI::new(Vector2I::new(-1, -1), Vector2I::new(3, 2));
+        let coords = vec![
+            Vector2I::new(0, 0),
+            Vector2I::new(-1, -1),
+            Vector2I::new(0, 0),
+            Vector2I::new(2, 0),
+            Vector2I::new(1, -2),
+            Vector2I::new(0, 0),
+            Vector2I::new(1, -1),
+        ];
+        let histogram = DenseTileMap::histogram(rect, coords);
+        assert_eq!(histogram.rect, rect);
+        assert_eq!(histogram.data, vec![1, 0, 1, 0, 3, 0]);
+    }
+
+    #[test]
+    fn test_fill_rect() {
+        let mut map = test_map();
+        map.fill_rect(RectI::new(Vector2I::new(2, -1), Vector2I::new(3, 3)), -1);
+        map.fill_rect(RectI::new(Vector2I::new(-4, -4), Vector2I::new(2, 8)), -1);
+        assert_eq!(map.data, (0..9).collect::<Vec<_>>());
+
+        map.fill_rect(RectI::new(Vector2I::new(0, -3), Vector2I::new(4, 4)), -1);
+        assert_eq!(map.data, vec![0, -1, -1, 3, -1, -1, 6, 7, 8]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let map = test_map();
+        let json = serde_json::to_string(&map).unwrap();
+        let round_tripped: DenseTileMap<i32> = serde_json::from_str(&json).unwrap();
+        assert_eq!(round_tripped.rect, map.rect);
+        assert_eq!(round_tripped.data, map.data);
+
+        let truncated = r#"{"rect":[-1,-1,3,3],"data":[0,1,2]}"#;
+        assert!(serde_json::from_str::<DenseTileMap<i32>>(truncated).is_err());
+        let negative = r#"{"rect":[0,0,-1,-1],"data":[0]}"#;
+        assert!(serde_json::from_str::<DenseTileMap<i32>>(negative).is_err());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let map = test_map();
+        let borrowed: Vec<i32> = (&map).into_iter().cloned().collect();
+        let rect = map.rect;
+        let owned: Vec<i32> = map.into_iter().collect();
+        assert_eq!(borrowed, owned);
+        let map = DenseTileMap::from_data(owned, rect);
+        assert_eq!(map.data, (0..9).collect::<Vec<_>>());
     }
 }
@@ -13,7 +13,7 @@
 use crate::gpu_data::SolidTileBatchPrimitive;
 use crate::paint;
 use crate::scene::PathObject;
-use crate::tile_map::DenseTileMap;
+use crate::tile_map::{DenseTileMap, TileMap};
 use crate::tiles;
 use pathfinder_geometry::basic::vector::Vector2I;
 use pathfinder_geometry::basic::rect::RectF;
@@ -56,6 +56,17 @@ impl ZBuffer {
         }
     }
 
+    /// Returns, for each tile, the index of the frontmost object that completely covers it, or
+    /// `None` if no object does. Objects behind the occluder are culled from that tile.
+    pub fn occluders(&self) -> DenseTileMap<Option<u32>> {
+        self.buffer.map(|depth| {
+            match depth.load(AtomicOrdering::Relaxed) {
+                0 => None,
+                depth => Some((depth - 1) as u32),
+            }
+        })
+    }
+
     pub fn build_solid_tiles(&self, paths: &[PathObject], object_range: Range<u32>)
                              -> Vec<SolidTileBatchPrimitive> {
         let mut solid_tiles = vec![];
@@ -94,3 +105,30 @@ impl SolidTileBatchPrimitive {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use crate::tile_map::TileMap;
+    use pathfinder_geometry::basic::rect::RectF;
+    use pathfinder_geometry::basic::vector::{Vector2F, Vector2I};
+    use super::ZBuffer;
+
+    #[test]
+    fn test_occluders() {
+        let z_buffer = ZBuffer::new(RectF::new(Vector2F::default(), Vector2F::new(64.0, 32.0)));
+        z_buffer.update(Vector2I::new(1, 0), 3);
+        z_buffer.update(Vector2I::new(1, 0), 1);
+        z_buffer.update(Vector2I::new(2, 1), 0);
+
+        let occluders = z_buffer.occluders();
+        assert_eq!(occluders.data.len(), 8);
+        for (tile_index, &occluder) in occluders.data.iter().enumerate() {
+            let expected = match occluders.index_to_coords(tile_index) {
+                coords if coords == Vector2I::new(1, 0) => Some(3),
+                coords if coords == Vector2I::new(2, 1) => Some(0),
+                _ => None,
+            };
+            assert_eq!(occluder, expected);
+        }
+    }
+}
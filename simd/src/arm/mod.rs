@@ -226,6 +226,11 @@ impl I32x4 {
         unsafe { I32x4(simd_fmin(self.0, other.0)) }
     }
 
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(simd_fmax(self.0, other.0)) }
+    }
+
     // Packed comparisons
 
     #[inline]
@@ -277,6 +277,16 @@ impl I32x4 {
         ])
     }
 
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        I32x4([
+            self[0].max(other[0]),
+            self[1].max(other[1]),
+            self[2].max(other[2]),
+            self[3].max(other[3]),
+        ])
+    }
+
     // Packed comparisons
 
     #[inline]
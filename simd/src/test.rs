@@ -393,6 +393,7 @@ fn test_i32x4_basic_ops() {
     let a = I32x4::new(6, 29, -40, 2);
     let b = I32x4::new(10, -5, 10, 46);
     assert_eq!(a.min(b), I32x4::new(6, -5, -40, 2));
+    assert_eq!(a.max(b), I32x4::new(10, 29, 10, 46));
 }
 
 #[test]
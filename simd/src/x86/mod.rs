@@ -277,6 +277,11 @@ impl I32x4 {
         unsafe { I32x4(x86_64::_mm_min_epi32(self.0, other.0)) }
     }
 
+    #[inline]
+    pub fn max(self, other: I32x4) -> I32x4 {
+        unsafe { I32x4(x86_64::_mm_max_epi32(self.0, other.0)) }
+    }
+
     // Packed comparisons
 
     #[inline]
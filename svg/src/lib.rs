@@ -18,6 +18,7 @@ use pathfinder_geometry::basic::vector::Vector2F;
 use pathfinder_geometry::basic::rect::RectF;
 use pathfinder_geometry::basic::transform2d::{Transform2DF, Transform2DFPathIter};
 use pathfinder_geometry::color::ColorU;
+use pathfinder_geometry::dash::OutlineDash;
 use pathfinder_geometry::outline::Outline;
 use pathfinder_geometry::segment::{Segment, SegmentFlags};
 use pathfinder_geometry::stroke::{LineCap, LineJoin, OutlineStrokeToFill, StrokeStyle};
@@ -27,18 +28,22 @@ use std::fmt::{Display, Formatter, Result as FormatResult};
 use std::mem;
 use usvg::{Color as SvgColor, LineCap as UsvgLineCap, LineJoin as UsvgLineJoin, Node, NodeExt};
 use usvg::{NodeKind, Opacity, Paint as UsvgPaint, PathSegment as UsvgPathSegment};
-use usvg::{Rect as UsvgRect, Transform as UsvgTransform, Tree, Visibility};
+use usvg::{Rect as UsvgRect, Transform as UsvgTransform, Tree, Units, Visibility};
 
 const HAIRLINE_STROKE_WIDTH: f32 = 0.0333;
 
+// Dash patterns that would split a path into more dashes than this are drawn solid instead.
+const MAX_DASH_COUNT: f32 = 10000.0;
+
 pub struct BuiltSVG {
     pub scene: Scene,
     pub result_flags: BuildResultFlags,
+    pub result_counts: BuildResultCounts,
 }
 
 bitflags! {
-    // NB: If you change this, make sure to update the `Display`
-    // implementation as well.
+    // NB: If you change this, make sure to update `NAMES` and
+    // `BUILD_RESULT_FLAG_COUNT` as well.
     pub struct BuildResultFlags: u16 {
         const UNSUPPORTED_CLIP_PATH_NODE       = 0x0001;
         const UNSUPPORTED_DEFS_NODE            = 0x0002;
@@ -55,17 +60,31 @@ bitflags! {
         const UNSUPPORTED_FILTER_ATTR          = 0x1000;
         const UNSUPPORTED_MASK_ATTR            = 0x2000;
         const UNSUPPORTED_OPACITY_ATTR         = 0x4000;
+        const UNSUPPORTED_DASHARRAY_ATTR       = 0x8000;
     }
 }
 
+// Must match the number of flags in `BuildResultFlags`.
+const BUILD_RESULT_FLAG_COUNT: usize = 16;
+
+/// How many times each unsupported feature was skipped while building the scene.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct BuildResultCounts {
+    counts: [u32; BUILD_RESULT_FLAG_COUNT],
+}
+
 impl BuiltSVG {
     // TODO(pcwalton): Allow a global transform to be set.
     pub fn from_tree(tree: Tree) -> BuiltSVG {
-        let global_transform = Transform2DF::default();
+        let global_state = State {
+            transform: Transform2DF::default(),
+            clip_polygons: vec![],
+        };
 
         let mut built_svg = BuiltSVG {
             scene: Scene::new(),
             result_flags: BuildResultFlags::empty(),
+            result_counts: BuildResultCounts::default(),
         };
 
         let root = &tree.root();
@@ -73,7 +92,7 @@ impl BuiltSVG {
             NodeKind::Svg(ref svg) => {
                 built_svg.scene.set_view_box(usvg_rect_to_euclid_rect(&svg.view_box.rect));
                 for kid in root.children() {
-                    built_svg.process_node(&kid, &global_transform);
+                    built_svg.process_node(&tree, &kid, &global_state);
                 }
             }
             _ => unreachable!(),
@@ -83,34 +102,37 @@ impl BuiltSVG {
         // recursively dropping reference counts on very large SVGs. :(
         mem::forget(tree);
 
+        built_svg.result_flags = built_svg.result_counts.flags();
         built_svg
     }
 
-    fn process_node(&mut self, node: &Node, transform: &Transform2DF) {
+    fn process_node(&mut self, tree: &Tree, node: &Node, state: &State) {
         let node_transform = usvg_transform_to_transform_2d(&node.transform());
-        let transform = transform.pre_mul(&node_transform);
+        let transform = state.transform.pre_mul(&node_transform);
 
         match *node.borrow() {
             NodeKind::Group(ref group) => {
-                if group.clip_path.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_CLIP_PATH_ATTR);
+                let mut state = State { transform, clip_polygons: state.clip_polygons.clone() };
+                if let Some(ref clip_path_id) = group.clip_path {
+                    match clip_polygon(tree, clip_path_id, &transform) {
+                        Some(clip_polygon) => state.clip_polygons.push(clip_polygon),
+                        None => {
+                            self.result_counts.record(BuildResultFlags::UNSUPPORTED_CLIP_PATH_ATTR)
+                        }
+                    }
                 }
                 if group.filter.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
+                    self.result_counts.record(BuildResultFlags::UNSUPPORTED_FILTER_ATTR);
                 }
                 if group.mask.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
+                    self.result_counts.record(BuildResultFlags::UNSUPPORTED_MASK_ATTR);
                 }
                 if group.opacity.is_some() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_OPACITY_ATTR);
+                    self.result_counts.record(BuildResultFlags::UNSUPPORTED_OPACITY_ATTR);
                 }
 
                 for kid in node.children() {
-                    self.process_node(&kid, &transform)
+                    self.process_node(tree, &kid, &state)
                 }
             }
             NodeKind::Path(ref path) if path.visibility == Visibility::Visible => {
@@ -118,12 +140,13 @@ impl BuiltSVG {
                     let style = self.scene.push_paint(&Paint::from_svg_paint(
                         &fill.paint,
                         fill.opacity,
-                        &mut self.result_flags,
+                        &mut self.result_counts,
                     ));
 
                     let path = UsvgPathToSegments::new(path.segments.iter().cloned());
                     let path = Transform2DFPathIter::new(path, &transform);
-                    let outline = Outline::from_segments(path);
+                    let mut outline = Outline::from_segments(path);
+                    state.clip_outline(&mut outline);
 
                     let name = format!("Fill({})", node.id());
                     self.scene.push_path(PathObject::new(outline, style, name));
@@ -133,7 +156,7 @@ impl BuiltSVG {
                     let style = self.scene.push_paint(&Paint::from_svg_paint(
                         &stroke.paint,
                         stroke.opacity,
-                        &mut self.result_flags,
+                        &mut self.result_counts,
                     ));
 
                     let stroke_style = StrokeStyle {
@@ -144,62 +167,166 @@ impl BuiltSVG {
                     };
 
                     let path = UsvgPathToSegments::new(path.segments.iter().cloned());
-                    let outline = Outline::from_segments(path);
+                    let mut outline = Outline::from_segments(path);
+
+                    if let Some(ref dasharray) = stroke.dasharray {
+                        let dashes = dash_pattern(dasharray, &outline, &mut self.result_counts);
+                        if let Some(dashes) = dashes {
+                            let offset = stroke.dashoffset as f32;
+                            let mut dash = OutlineDash::new(&outline, &dashes, offset);
+                            dash.dash();
+                            outline = dash.into_outline();
+                        }
+                    }
 
                     let mut stroke_to_fill = OutlineStrokeToFill::new(&outline, stroke_style);
                     stroke_to_fill.offset();
                     let mut outline = stroke_to_fill.into_outline();
                     outline.transform(&transform);
+                    state.clip_outline(&mut outline);
 
                     let name = format!("Stroke({})", node.id());
                     self.scene.push_path(PathObject::new(outline, style, name));
                 }
             }
             NodeKind::Path(..) => {}
-            NodeKind::ClipPath(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_CLIP_PATH_NODE);
-            }
+            // Clip paths are handled where they are referenced.
+            NodeKind::ClipPath(..) => {}
             NodeKind::Defs { .. } => {
-                if node.has_children() {
-                    self.result_flags
-                        .insert(BuildResultFlags::UNSUPPORTED_DEFS_NODE);
+                let is_clip_path = |kid: &Node| match *kid.borrow() {
+                    NodeKind::ClipPath(..) => true,
+                    _ => false,
+                };
+                if !node.children().all(|kid| is_clip_path(&kid)) {
+                    self.result_counts.record(BuildResultFlags::UNSUPPORTED_DEFS_NODE);
                 }
             }
             NodeKind::Filter(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_FILTER_NODE);
             }
             NodeKind::Image(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_IMAGE_NODE);
             }
             NodeKind::LinearGradient(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_LINEAR_GRADIENT_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_LINEAR_GRADIENT_NODE);
             }
             NodeKind::Mask(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_MASK_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_MASK_NODE);
             }
             NodeKind::Pattern(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_PATTERN_NODE);
             }
             NodeKind::RadialGradient(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_RADIAL_GRADIENT_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_RADIAL_GRADIENT_NODE);
             }
             NodeKind::Svg(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_NESTED_SVG_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_NESTED_SVG_NODE);
             }
             NodeKind::Text(..) => {
-                self.result_flags
-                    .insert(BuildResultFlags::UNSUPPORTED_TEXT_NODE);
+                self.result_counts.record(BuildResultFlags::UNSUPPORTED_TEXT_NODE);
+            }
+        }
+    }
+}
+
+// The inherited state that applies to a node while building the scene.
+struct State {
+    // Where paths are going to get transformed to.
+    transform: Transform2DF,
+    // Convex polygons, in scene space, that paths are clipped against.
+    clip_polygons: Vec<Vec<Vector2F>>,
+}
+
+impl State {
+    fn clip_outline(&self, outline: &mut Outline) {
+        for clip_polygon in &self.clip_polygons {
+            if outline.is_outside_polygon(clip_polygon) {
+                *outline = Outline::new();
+                return;
+            }
+            outline.clip_against_polygon(clip_polygon);
+        }
+    }
+}
+
+// Resolves a `clip-path` reference to a convex polygon in scene space, wound so that
+// `Outline::clip_against_polygon()` keeps its interior.
+//
+// Only clip paths in user space consisting of a single straight-edged convex path are supported.
+// Anything else returns `None`.
+fn clip_polygon(tree: &Tree, clip_path_id: &str, transform: &Transform2DF)
+                -> Option<Vec<Vector2F>> {
+    let clip_node = tree.defs_by_id(clip_path_id)?;
+    let transform = match *clip_node.borrow() {
+        NodeKind::ClipPath(ref clip_path) => {
+            match clip_path.units {
+                Units::UserSpaceOnUse if clip_path.clip_path.is_none() => {}
+                _ => return None,
+            }
+            transform.pre_mul(&usvg_transform_to_transform_2d(&clip_path.transform))
+        }
+        _ => return None,
+    };
+
+    let mut kids = clip_node.children();
+    let kid = match (kids.next(), kids.next()) {
+        (Some(kid), None) => kid,
+        _ => return None,
+    };
+    let transform = transform.pre_mul(&usvg_transform_to_transform_2d(&kid.transform()));
+
+    let mut points = vec![];
+    match *kid.borrow() {
+        NodeKind::Path(ref path) => {
+            for (segment_index, segment) in path.segments.iter().enumerate() {
+                match *segment {
+                    UsvgPathSegment::MoveTo { x, y } if segment_index == 0 => {
+                        points.push(Vector2F::new(x as f32, y as f32))
+                    }
+                    UsvgPathSegment::LineTo { x, y } => {
+                        points.push(Vector2F::new(x as f32, y as f32))
+                    }
+                    UsvgPathSegment::ClosePath if segment_index == path.segments.len() - 1 => {}
+                    _ => return None,
+                }
             }
         }
+        _ => return None,
+    }
+    for point in &mut points {
+        *point = transform.transform_point(*point);
     }
+
+    wind_convex_clip_polygon(points)
+}
+
+// Winds a closed polygon the way `Outline::clip_against_polygon()` expects, dropping a final
+// point that repeats the first. Returns `None` if the polygon is concave or has fewer than three
+// points.
+fn wind_convex_clip_polygon(mut points: Vec<Vector2F>) -> Option<Vec<Vector2F>> {
+    if points.len() > 1 && points.first() == points.last() {
+        points.pop();
+    }
+    if points.len() < 3 {
+        return None;
+    }
+
+    let (mut min_turn, mut max_turn, mut area) = (0.0, 0.0, 0.0);
+    for (index, &point) in points.iter().enumerate() {
+        let next = points[(index + 1) % points.len()];
+        let next_next = points[(index + 2) % points.len()];
+        let turn = (next - point).det(next_next - next);
+        min_turn = f32::min(min_turn, turn);
+        max_turn = f32::max(max_turn, turn);
+        area += point.det(next);
+    }
+    if min_turn < 0.0 && max_turn > 0.0 {
+        return None;
+    }
+    if area < 0.0 {
+        points.reverse();
+    }
+    Some(points)
 }
 
 impl Display for BuildResultFlags {
@@ -221,44 +348,98 @@ impl Display for BuildResultFlags {
             formatter.write_str(name)?;
         }
 
-        return Ok(());
-
-        // Must match the order in `BuildResultFlags`.
-        static NAMES: &'static [&'static str] = &[
-            "<clipPath>",
-            "<defs>",
-            "<filter>",
-            "<image>",
-            "<linearGradient>",
-            "<mask>",
-            "<pattern>",
-            "<radialGradient>",
-            "nested <svg>",
-            "<text>",
-            "paint server element",
-            "clip-path attribute",
-            "filter attribute",
-            "mask attribute",
-            "opacity attribute",
-        ];
+        Ok(())
+    }
+}
+
+impl BuildResultCounts {
+    /// Returns the number of times the given feature was skipped. `flag` must contain exactly one
+    /// flag.
+    #[inline]
+    pub fn get(&self, flag: BuildResultFlags) -> u32 {
+        debug_assert_eq!(flag.bits().count_ones(), 1);
+        self.counts[flag.bits().trailing_zeros() as usize]
+    }
+
+    /// Returns the set of features that were skipped at least once.
+    pub fn flags(&self) -> BuildResultFlags {
+        let mut flags = BuildResultFlags::empty();
+        for (bit, &count) in self.counts.iter().enumerate() {
+            if count > 0 {
+                flags.insert(BuildResultFlags::from_bits_truncate(1 << bit));
+            }
+        }
+        flags
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+
+    #[inline]
+    fn record(&mut self, flag: BuildResultFlags) {
+        self.counts[flag.bits().trailing_zeros() as usize] += 1;
     }
 }
 
+impl Display for BuildResultCounts {
+    fn fmt(&self, formatter: &mut Formatter) -> FormatResult {
+        let mut first = true;
+        for (&count, name) in self.counts.iter().zip(NAMES.iter()) {
+            if count == 0 {
+                continue;
+            }
+            if !first {
+                formatter.write_str(", ")?;
+            } else {
+                first = false;
+            }
+            write!(formatter, "{} ({})", name, count)?;
+        }
+        Ok(())
+    }
+}
+
+// Must match the order in `BuildResultFlags`.
+static NAMES: &'static [&'static str] = &[
+    "<clipPath>",
+    "<defs>",
+    "<filter>",
+    "<image>",
+    "<linearGradient>",
+    "<mask>",
+    "<pattern>",
+    "<radialGradient>",
+    "nested <svg>",
+    "<text>",
+    "paint server element",
+    "clip-path attribute",
+    "filter attribute",
+    "mask attribute",
+    "opacity attribute",
+    "stroke-dasharray attribute",
+];
+
 trait PaintExt {
-    fn from_svg_paint(svg_paint: &UsvgPaint, opacity: Opacity, result_flags: &mut BuildResultFlags)
+    fn from_svg_paint(svg_paint: &UsvgPaint,
+                      opacity: Opacity,
+                      result_counts: &mut BuildResultCounts)
                       -> Self;
 }
 
 impl PaintExt for Paint {
     #[inline]
-    fn from_svg_paint(svg_paint: &UsvgPaint, opacity: Opacity, result_flags: &mut BuildResultFlags)
+    fn from_svg_paint(svg_paint: &UsvgPaint,
+                      opacity: Opacity,
+                      result_counts: &mut BuildResultCounts)
                       -> Paint {
         Paint {
             color: match *svg_paint {
                 UsvgPaint::Color(color) => ColorU::from_svg_color(color, opacity),
                 UsvgPaint::Link(_) => {
                     // TODO(pcwalton)
-                    result_flags.insert(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
+                    result_counts.record(BuildResultFlags::UNSUPPORTED_LINK_PAINT);
                     ColorU::black()
                 }
             },
@@ -266,6 +447,38 @@ impl PaintExt for Paint {
     }
 }
 
+// Returns the dash pattern to apply to the outline, or `None` if the stroke should be drawn solid.
+//
+// Per the SVG specification, negative and all-zero patterns render as a solid stroke, and odd
+// patterns are repeated to make an even number of entries.
+fn dash_pattern(dasharray: &[f64], outline: &Outline, result_counts: &mut BuildResultCounts)
+                -> Option<Vec<f32>> {
+    let mut dashes: Vec<f32> = dasharray.iter().map(|&dash| dash as f32).collect();
+    if dashes.iter().any(|&dash| dash < 0.0) {
+        return None;
+    }
+    if dashes.len() % 2 == 1 {
+        dashes.extend_from_slice(&dashes.clone());
+    }
+    let pattern_length: f32 = dashes.iter().sum();
+    if pattern_length <= 0.0 {
+        return None;
+    }
+
+    let outline_length: f32 = outline.contours()
+                                     .iter()
+                                     .flat_map(|contour| contour.iter())
+                                     .map(|segment| segment.arc_length())
+                                     .sum();
+    let dash_count = outline_length / pattern_length * (dashes.len() / 2) as f32;
+    if dash_count > MAX_DASH_COUNT {
+        result_counts.record(BuildResultFlags::UNSUPPORTED_DASHARRAY_ATTR);
+        return None;
+    }
+
+    Some(dashes)
+}
+
 fn usvg_rect_to_euclid_rect(rect: &UsvgRect) -> RectF {
     RectF::new(
         Vector2F::new(rect.x as f32, rect.y as f32),
@@ -414,3 +627,114 @@ impl LineJoinExt for LineJoin {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use pathfinder_geometry::basic::rect::RectF;
+    use pathfinder_geometry::basic::vector::Vector2F;
+    use pathfinder_geometry::outline::{Contour, Outline};
+    use super::{BuildResultCounts, BuildResultFlags, dash_pattern, wind_convex_clip_polygon};
+
+    fn line(length: f32) -> Outline {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_endpoint(Vector2F::new(length, 0.0));
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline
+    }
+
+    #[test]
+    fn test_dash_pattern() {
+        let mut counts = BuildResultCounts::default();
+        assert_eq!(dash_pattern(&[4.0, 2.0], &line(100.0), &mut counts), Some(vec![4.0, 2.0]));
+        assert!(counts.is_empty());
+    }
+
+    #[test]
+    fn test_dash_pattern_solid() {
+        let mut counts = BuildResultCounts::default();
+        assert_eq!(dash_pattern(&[0.0, 0.0], &line(100.0), &mut counts), None);
+        assert_eq!(dash_pattern(&[0.0], &line(100.0), &mut counts), None);
+        assert_eq!(dash_pattern(&[4.0, -1.0], &line(100.0), &mut counts), None);
+        assert_eq!(dash_pattern(&[-4.0, 0.0], &line(100.0), &mut counts), None);
+        assert!(counts.is_empty());
+    }
+
+    #[test]
+    fn test_dash_pattern_odd() {
+        let mut counts = BuildResultCounts::default();
+        assert_eq!(dash_pattern(&[5.0], &line(100.0), &mut counts), Some(vec![5.0, 5.0]));
+        assert_eq!(dash_pattern(&[1.0, 2.0, 3.0], &line(100.0), &mut counts),
+                   Some(vec![1.0, 2.0, 3.0, 1.0, 2.0, 3.0]));
+        assert!(counts.is_empty());
+    }
+
+    #[test]
+    fn test_dash_pattern_too_many_dashes() {
+        // 0.001-unit dashes and gaps along a 100-unit line would make 50,000 dashes.
+        let mut counts = BuildResultCounts::default();
+        assert_eq!(dash_pattern(&[0.01], &line(100.0), &mut counts), Some(vec![0.01, 0.01]));
+        assert_eq!(dash_pattern(&[0.001], &line(100.0), &mut counts), None);
+        assert_eq!(counts.get(BuildResultFlags::UNSUPPORTED_DASHARRAY_ATTR), 1);
+        assert_eq!(counts.flags(), BuildResultFlags::UNSUPPORTED_DASHARRAY_ATTR);
+    }
+
+    // Clips a 100-unit square against `clip_polygon` and returns the bounds of what's left.
+    fn clipped_square_bounds(clip_polygon: &[Vector2F]) -> RectF {
+        let mut contour = Contour::new();
+        contour.push_endpoint(Vector2F::new(0.0, 0.0));
+        contour.push_endpoint(Vector2F::new(100.0, 0.0));
+        contour.push_endpoint(Vector2F::new(100.0, 100.0));
+        contour.push_endpoint(Vector2F::new(0.0, 100.0));
+        contour.close();
+        let mut outline = Outline::new();
+        outline.push_contour(contour);
+        outline.clip_against_polygon(clip_polygon);
+        outline.bounds()
+    }
+
+    #[test]
+    fn test_wind_convex_clip_polygon() {
+        let clockwise = vec![
+            Vector2F::new(20.0, 20.0),
+            Vector2F::new(60.0, 20.0),
+            Vector2F::new(60.0, 60.0),
+            Vector2F::new(20.0, 60.0),
+        ];
+        let counterclockwise: Vec<_> = clockwise.iter().cloned().rev().collect();
+
+        // Whichever way the input is wound, the result must keep the polygon's interior.
+        for points in vec![clockwise.clone(), counterclockwise] {
+            let clip_polygon = wind_convex_clip_polygon(points).unwrap();
+            assert_eq!(clip_polygon.len(), 4);
+            let bounds = clipped_square_bounds(&clip_polygon);
+            assert!((bounds.origin() - Vector2F::splat(20.0)).length() < 0.01);
+            assert!((bounds.lower_right() - Vector2F::splat(60.0)).length() < 0.01);
+        }
+
+        // An explicitly closed polygon loses its repeated point.
+        let mut closed = clockwise.clone();
+        closed.push(clockwise[0]);
+        assert_eq!(wind_convex_clip_polygon(closed).map(|points| points.len()), Some(4));
+    }
+
+    #[test]
+    fn test_wind_convex_clip_polygon_rejects_concave() {
+        let concave = vec![
+            Vector2F::new(20.0, 20.0),
+            Vector2F::new(60.0, 20.0),
+            Vector2F::new(40.0, 30.0),
+            Vector2F::new(60.0, 60.0),
+            Vector2F::new(20.0, 60.0),
+        ];
+        assert_eq!(wind_convex_clip_polygon(concave.clone()), None);
+        assert_eq!(wind_convex_clip_polygon(concave.into_iter().rev().collect()), None);
+    }
+
+    #[test]
+    fn test_wind_convex_clip_polygon_rejects_degenerate() {
+        let line = vec![Vector2F::new(20.0, 20.0), Vector2F::new(60.0, 20.0)];
+        assert_eq!(wind_convex_clip_polygon(line), None);
+    }
+}